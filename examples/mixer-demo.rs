@@ -30,8 +30,12 @@ fn demo(music_file: &Path, sound_file: Option<&Path>) -> Result<(), String> {
     let channels = DEFAULT_CHANNELS; // Stereo
     let chunk_size = 1_024;
     sdl2::mixer::open_audio(frequency, format, channels, chunk_size)?;
-    let _mixer_context =
+    let mixer_context =
         sdl2::mixer::init(InitFlag::MP3 | InitFlag::FLAC | InitFlag::MOD | InitFlag::OGG)?;
+    println!(
+        "loaded decoders: {}",
+        mixer_context.loaded_flags().to_string()
+    );
 
     // Number of mixing channels available for sound effect `Chunk`s to play
     // simultaneously.
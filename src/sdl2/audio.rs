@@ -133,6 +133,38 @@ impl AudioSubsystem {
         }
     }
 
+    /// Switches the audio subsystem to another driver, one of `drivers()`,
+    /// for example to pick ALSA over PulseAudio. The devices opened with the
+    /// previous driver are closed, so this should be called before opening
+    /// any, including with `mixer::open_audio`.
+    ///
+    /// Returns an error if SDL2 wasn't built with the driver or it can't be
+    /// initialized, in which case the previous driver is kept.
+    #[doc(alias = "SDL_AudioInit")]
+    pub fn set_audio_driver(&self, driver: &str) -> Result<(), String> {
+        if !drivers().any(|name| name == driver) {
+            return Err(format!("Audio driver {} isn't available", driver));
+        }
+        let driver = CString::new(driver).map_err(|err| err.to_string())?;
+        let previous = unsafe {
+            let buf = sys::SDL_GetCurrentAudioDriver();
+            if buf.is_null() {
+                None
+            } else {
+                Some(CStr::from_ptr(buf as *const _).to_owned())
+            }
+        };
+        if unsafe { sys::SDL_AudioInit(driver.as_ptr()) } == 0 {
+            return Ok(());
+        }
+        let error = get_error();
+        // a driver that failed to initialize leaves no driver at all
+        if let Some(previous) = previous {
+            unsafe { sys::SDL_AudioInit(previous.as_ptr()) };
+        }
+        Err(error)
+    }
+
     #[doc(alias = "SDL_GetNumAudioDevices")]
     pub fn num_audio_playback_devices(&self) -> Option<u32> {
         let result = unsafe { sys::SDL_GetNumAudioDevices(0) };
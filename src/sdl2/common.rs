@@ -1,5 +1,8 @@
 use std::error::Error;
+use std::ffi::CString;
 use std::fmt;
+use std::io::Read;
+use std::path::Path;
 
 /// A given integer was so big that its representation as a C integer would be
 /// negative.
@@ -8,6 +11,44 @@ pub enum IntegerOrSdlError {
     IntegerOverflows(&'static str, u32),
     SdlError(String),
 }
+/// Converts the given path to a C string, failing on paths that are not
+/// valid UTF-8 or that contain a nul byte.
+pub fn path_to_cstring(path: &Path) -> Result<CString, String> {
+    let path_str = path
+        .to_str()
+        .ok_or_else(|| format!("Path is not valid UTF-8: {}", path.display()))?;
+    CString::new(path_str).map_err(|_| format!("Path contains a nul byte: {}", path.display()))
+}
+
+/// Reads all the data of the given reader, failing with a clear message if
+/// there is none or if there is more than `max_size` bytes, so that a
+/// truncated or endless stream doesn't reach SDL. `what` names the data in
+/// the error messages.
+pub fn read_to_end_limited<R: Read>(
+    reader: R,
+    max_size: Option<usize>,
+    what: &str,
+) -> Result<Vec<u8>, String> {
+    let mut data = Vec::new();
+    let result = match max_size {
+        // read one byte more than allowed to detect oversized data
+        Some(max_size) => reader.take(max_size as u64 + 1).read_to_end(&mut data),
+        None => {
+            let mut reader = reader;
+            reader.read_to_end(&mut data)
+        }
+    };
+    result.map_err(|err| format!("IO error: {}", err))?;
+    match max_size {
+        _ if data.is_empty() => Err(format!("empty {} data", what)),
+        Some(max_size) if data.len() > max_size => Err(format!(
+            "{} data is larger than the limit of {} bytes",
+            what, max_size
+        )),
+        _ => Ok(data),
+    }
+}
+
 /// Validates and converts the given u32 to a positive C integer.
 pub fn validate_int(value: u32, name: &'static str) -> Result<::libc::c_int, IntegerOrSdlError> {
     use self::IntegerOrSdlError::*;
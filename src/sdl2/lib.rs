@@ -60,6 +60,8 @@ pub extern crate sdl2_sys as sys;
 
 #[cfg(feature = "gfx")]
 extern crate c_vec;
+#[cfg(feature = "unicode-normalization")]
+extern crate unicode_normalization;
 
 pub use crate::sdl::*;
 
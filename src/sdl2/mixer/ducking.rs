@@ -0,0 +1,118 @@
+use std::sync::{Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use super::{Channel, Music};
+
+/// How often the music is ducked or restored by `set_ducking`, in
+/// milliseconds.
+pub const DUCKING_POLL_MS: u32 = 10;
+
+#[derive(Clone, Copy)]
+struct Ducking {
+    trigger: Channel,
+    scale: f32,
+    attack_ms: u32,
+    release_ms: u32,
+}
+
+#[derive(Default)]
+struct DuckingState {
+    ducking: Option<Ducking>,
+    thread: Option<JoinHandle<()>>,
+}
+
+lazy_static! {
+    static ref DUCKING: Mutex<DuckingState> = Mutex::new(DuckingState::default());
+}
+
+fn state() -> MutexGuard<'static, DuckingState> {
+    DUCKING.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Lowers the volume of the music to `target_volume_scale` times its volume
+/// while the `trigger` channel plays, for dialogue over music, and restores
+/// it once the channel stops.
+///
+/// The volume ramps down over `attack_ms` milliseconds and back up over
+/// `release_ms` milliseconds. A background thread polls the trigger channel
+/// and sets the volume of the music every `DUCKING_POLL_MS` milliseconds, so
+/// ducking starts up to that long after the channel does. The volume set
+/// with `Music::set_volume` in the meantime is taken as the new volume to
+/// duck from. Calling this again replaces the previous settings.
+pub fn set_ducking(trigger: Channel, target_volume_scale: f32, attack_ms: u32, release_ms: u32) {
+    let scale = if target_volume_scale.is_nan() {
+        0.0
+    } else {
+        target_volume_scale.clamp(0.0, 1.0)
+    };
+    let mut state = state();
+    state.ducking = Some(Ducking {
+        trigger,
+        scale,
+        attack_ms,
+        release_ms,
+    });
+    if state.thread.is_none() {
+        state.thread = Some(thread::spawn(duck_music));
+    }
+}
+
+/// Stops the ducking started with `set_ducking`, restoring the volume of the
+/// music.
+pub fn unset_ducking() {
+    let thread = {
+        let mut state = state();
+        state.ducking = None;
+        state.thread.take()
+    };
+    if let Some(thread) = thread {
+        let _ = thread.join();
+    }
+}
+
+fn duck_music() {
+    let mut base = Music::get_volume();
+    let mut written = base;
+    let mut gain = 1.0f32;
+    loop {
+        let ducking = match state().ducking {
+            Some(ducking) => ducking,
+            None => break,
+        };
+        let volume = Music::get_volume();
+        if volume != written {
+            base = volume;
+        }
+
+        let (target, ramp_ms) = if ducking.trigger.is_playing() {
+            (ducking.scale, ducking.attack_ms)
+        } else {
+            (1.0, ducking.release_ms)
+        };
+        // the gain left by previous settings still ramps when not ducking
+        let span = if ducking.scale < 1.0 {
+            1.0 - ducking.scale
+        } else {
+            1.0
+        };
+        let step = if ramp_ms == 0 {
+            1.0
+        } else {
+            span * DUCKING_POLL_MS as f32 / ramp_ms as f32
+        };
+        gain = if gain > target {
+            (gain - step).max(target)
+        } else {
+            (gain + step).min(target)
+        };
+        written = (base as f32 * gain).round() as i32;
+        Music::set_volume(written);
+
+        thread::sleep(Duration::from_millis(u64::from(DUCKING_POLL_MS)));
+    }
+    // unless the volume was changed since
+    if Music::get_volume() == written {
+        Music::set_volume(base);
+    }
+}
@@ -0,0 +1,94 @@
+//! Bookkeeping of the effects registered on each channel, which `SDL2_mixer`
+//! can't report.
+//!
+//! Effects registered with a callback of this crate are tracked by the
+//! address of their data, and untracked by their "done" callback, which
+//! `SDL2_mixer` calls whenever it removes them: when they are unregistered,
+//! when the channel finishes playing, or when the audio is closed.
+//!
+//! The built-in effects of `SDL2_mixer` have no callback of ours, so a no-op
+//! marker effect is registered alongside them: it is removed at the same
+//! time as them, and its "done" callback untracks them.
+
+use libc::{c_int, c_void};
+use std::collections::{HashMap, HashSet};
+use std::ptr;
+use std::sync::{Mutex, MutexGuard};
+use sys::mixer;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EffectId {
+    Panning,
+    Distance,
+    Position,
+    ReverseStereo,
+    /// An effect registered with a callback of this crate, identified by the
+    /// address of its data.
+    Custom(usize),
+}
+
+impl EffectId {
+    fn is_builtin(self) -> bool {
+        !matches!(self, EffectId::Custom(_))
+    }
+}
+
+lazy_static! {
+    static ref REGISTERED: Mutex<HashMap<c_int, HashSet<EffectId>>> = Mutex::new(HashMap::new());
+}
+
+// the bookkeeping stays consistent even if a user effect panicked
+fn registered() -> MutexGuard<'static, HashMap<c_int, HashSet<EffectId>>> {
+    REGISTERED.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+unsafe extern "C" fn c_marker_effect(
+    _chan: c_int,
+    _stream: *mut c_void,
+    _len: c_int,
+    _udata: *mut c_void,
+) {
+}
+
+unsafe extern "C" fn c_marker_done(chan: c_int, _udata: *mut c_void) {
+    if let Some(effects) = registered().get_mut(&chan) {
+        effects.retain(|effect| !effect.is_builtin());
+    }
+}
+
+/// Records whether the given effect is registered on the channel.
+///
+/// `SDL2_mixer` is called after the bookkeeping is unlocked, as it may call
+/// back into it.
+pub fn set_registered(channel: c_int, effect: EffectId, is_registered: bool) {
+    let (had_builtins, has_builtins) = {
+        let mut registered = registered();
+        let effects = registered.entry(channel).or_default();
+        let had_builtins = effects.iter().any(|effect| effect.is_builtin());
+        if is_registered {
+            effects.insert(effect);
+        } else {
+            effects.remove(&effect);
+        }
+        let has_builtins = effects.iter().any(|effect| effect.is_builtin());
+        (had_builtins, has_builtins)
+    };
+    unsafe {
+        if has_builtins && !had_builtins {
+            mixer::Mix_RegisterEffect(
+                channel,
+                Some(c_marker_effect),
+                Some(c_marker_done),
+                ptr::null_mut(),
+            );
+        } else if had_builtins && !has_builtins {
+            mixer::Mix_UnregisterEffect(channel, Some(c_marker_effect));
+        }
+    }
+}
+
+pub fn has_effects(channel: c_int) -> bool {
+    registered()
+        .get(&channel)
+        .is_some_and(|effects| !effects.is_empty())
+}
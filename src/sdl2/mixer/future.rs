@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::os::raw::c_int;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+
+use super::Channel;
+
+#[derive(Default)]
+struct Completion {
+    done: bool,
+    waker: Option<Waker>,
+}
+
+type SharedCompletion = Arc<Mutex<Completion>>;
+
+lazy_static! {
+    static ref WAITING: Mutex<HashMap<c_int, Vec<SharedCompletion>>> = Mutex::new(HashMap::new());
+}
+
+fn waiting() -> MutexGuard<'static, HashMap<c_int, Vec<SharedCompletion>>> {
+    WAITING.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+fn lock(completion: &SharedCompletion) -> MutexGuard<'_, Completion> {
+    completion.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Completes the futures waiting on the channel, called from the channel
+/// finished hook.
+pub fn channel_finished(ch: c_int) {
+    let completions = waiting().remove(&ch).unwrap_or_default();
+    for completion in completions {
+        let waker = {
+            let mut completion = lock(&completion);
+            completion.done = true;
+            completion.waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// Returns whether futures are waiting on channels, which need the channel
+/// finished hook to stay set.
+pub fn has_waiting() -> bool {
+    !waiting().is_empty()
+}
+
+/// Makes a future wait for the chunk playing on the channel to finish.
+pub fn wait(channel: Channel) -> PlayFuture {
+    let completion = SharedCompletion::default();
+    waiting()
+        .entry(channel.0 as c_int)
+        .or_default()
+        .push(completion.clone());
+    PlayFuture {
+        channel,
+        completion,
+    }
+}
+
+/// A future resolving to the channel once the chunk played with
+/// `Channel::play_future` finishes or is halted.
+///
+/// It is woken by the channel finished hook of SDL2_mixer instead of polling
+/// the channel. The hook runs on the audio thread, so the future can be
+/// awaited on any executor.
+pub struct PlayFuture {
+    channel: Channel,
+    completion: SharedCompletion,
+}
+
+impl PlayFuture {
+    /// The channel the chunk is playing on.
+    pub fn channel(&self) -> Channel {
+        self.channel
+    }
+}
+
+impl Future for PlayFuture {
+    type Output = Channel;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Channel> {
+        let mut completion = lock(&self.completion);
+        // the chunk may have finished before the future started waiting, in
+        // which case the channel is idle
+        if completion.done || !self.channel.is_playing() {
+            return Poll::Ready(self.channel);
+        }
+        // the hook can't complete the future before the waker is stored,
+        // since it needs the lock held here
+        completion.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
@@ -19,24 +19,56 @@
 //! default-features = false
 //! features = ["mixer"]
 //! ```
+//!
+//! The functions of this module don't panic on any response of `SDL2_mixer`
+//! or on invalid input such as paths containing nul characters: failures are
+//! reported through `Result` or `Option`.
 
-use audio::AudioFormatNum;
+use audio::{AudioCVT, AudioFormatNum};
+use common::{path_to_cstring, read_to_end_limited};
 use get_error;
 use libc::c_void;
-use libc::{c_double, c_int, c_uint};
+use libc::{c_char, c_double, c_int, c_uint};
 use rwops::RWops;
 use std::borrow::ToOwned;
 use std::convert::TryInto;
 use std::default;
+use std::env;
+use std::error;
 use std::ffi::{CStr, CString};
 use std::fmt;
+use std::io::Read;
 use std::marker::PhantomData;
+use std::mem;
 use std::path::Path;
-use std::str::from_utf8;
+use std::ptr;
+use std::slice;
+use std::sync::atomic::{AtomicI32, AtomicU32, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
 use sys;
 use sys::mixer;
 use version::Version;
 
+mod ducking;
+mod effects;
+#[cfg(feature = "mixer-futures")]
+mod future;
+mod music_clock;
+mod pcm;
+mod playlist;
+mod speed;
+mod streaming;
+
+use self::effects::EffectId;
+
+pub use self::ducking::{set_ducking, unset_ducking, DUCKING_POLL_MS};
+#[cfg(feature = "mixer-futures")]
+pub use self::future::PlayFuture;
+pub use self::playlist::{Playlist, RepeatMode};
+pub use self::streaming::StreamingChunk;
+
 // This comes from SDL_audio.h
 #[allow(non_camel_case_types)]
 mod ll {
@@ -89,6 +121,9 @@ pub const DEFAULT_CHANNELS: i32 = 2;
 pub const DEFAULT_FREQUENCY: i32 = 22_050;
 /// Maximum value for any volume setting.
 pub const MAX_VOLUME: i32 = 128;
+/// A fade-out length in milliseconds for `Channel::halt_smooth`, short
+/// enough to sound like an immediate stop but long enough to avoid a click.
+pub const DEFAULT_HALT_RAMP_MS: u32 = 5;
 
 /// Returns the version of the dynamically linked `SDL_mixer` library
 pub fn get_linked_version() -> Version {
@@ -143,8 +178,34 @@ impl Drop for Sdl2MixerContext {
     }
 }
 
+impl Sdl2MixerContext {
+    /// Returns the decoders currently loaded, which may be fewer than the
+    /// ones requested from `init` if some of their libraries are missing.
+    pub fn loaded_flags(&self) -> InitFlag {
+        let ret = unsafe { mixer::Mix_Init(0) };
+        InitFlag::from_bits_truncate(ret as u32)
+    }
+
+    /// Returns an error naming the requested decoders that are not loaded.
+    pub fn check_flags(&self, flags: InitFlag) -> Result<(), String> {
+        let missing = flags - self.loaded_flags();
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(format!(
+                "Could not init: {}",
+                missing.to_string().trim_end()
+            ))
+        }
+    }
+}
+
 /// Loads dynamic libraries and prepares them for use.  Flags should be
 /// one or more flags from `InitFlag`.
+///
+/// This succeeds as long as one of the requested decoders could be loaded:
+/// use `Sdl2MixerContext::loaded_flags` or `Sdl2MixerContext::check_flags`
+/// to find out whether all of them were.
 pub fn init(flags: InitFlag) -> Result<Sdl2MixerContext, String> {
     let return_flags = unsafe {
         let ret = mixer::Mix_Init(flags.bits() as c_int);
@@ -172,6 +233,9 @@ pub fn init(flags: InitFlag) -> Result<Sdl2MixerContext, String> {
 ///                work very well on older systems. For instance, a chunk size of 256 will give
 ///                you a latency of 6ms, while a chunk size of 1024 will give you a latency of 23ms
 ///                for a frequency of 44100kHz.
+///
+/// The audio driver is the one of the audio subsystem, which can be picked
+/// beforehand with `AudioSubsystem::set_audio_driver`.
 pub fn open_audio(
     frequency: i32,
     format: AudioFormat,
@@ -198,6 +262,56 @@ pub fn close_audio() {
     unsafe { mixer::Mix_CloseAudio() }
 }
 
+/// Closes the audio device and opens it again with another format, for
+/// audio settings changed in game, like switching between mono and stereo.
+///
+/// The number of allocated and reserved channels, the channel finished
+/// callback and the master gain are kept. Everything playing is stopped,
+/// and the effects registered on channels are removed. The position of the
+/// music is saved, to play it from there with `Music::resume_at_last_position`.
+/// Chunks and music are decoded to the format of the device they were loaded
+/// for, so they should be loaded again if the format changed.
+pub fn reopen_audio(
+    frequency: i32,
+    format: AudioFormat,
+    channels: i32,
+    chunksize: i32,
+) -> Result<(), String> {
+    let allocated = allocate_channels(-1);
+    music_clock::save(Music::is_playing());
+    // the device is opened once per call to open_audio, and only closed by
+    // as many calls to close_audio
+    loop {
+        let opened =
+            unsafe { mixer::Mix_QuerySpec(ptr::null_mut(), ptr::null_mut(), ptr::null_mut()) };
+        if opened == 0 {
+            break;
+        }
+        close_audio();
+    }
+    open_audio(frequency, format, channels, chunksize)?;
+
+    allocate_channels(allocated);
+    reserve_channels(reserved_channels());
+    if let Some(callback) = unsafe { CHANNEL_FINISHED_CALLBACK } {
+        set_channel_finished(callback);
+    }
+    if channel_finished_ex().callback.is_some() {
+        set_channel_finished_hook();
+    }
+    #[cfg(feature = "mixer-futures")]
+    {
+        if future::has_waiting() {
+            set_channel_finished_hook();
+        }
+    }
+    // the master gain callback is given the format of the device
+    if master_gain() < 1.0 {
+        set_master_gain(master_gain())?;
+    }
+    Ok(())
+}
+
 /// Get the actual audio format in use by the opened audio device.
 pub fn query_spec() -> Result<(i32, AudioFormat, i32), String> {
     let mut frequency: c_int = 0;
@@ -218,13 +332,18 @@ pub fn get_chunk_decoders_number() -> i32 {
     unsafe { mixer::Mix_GetNumChunkDecoders() as i32 }
 }
 
-/// Get the name of the indexed sample chunk decoder.
+/// Get the name of the indexed sample chunk decoder, or an empty string if the index is out of
+/// range.
 pub fn get_chunk_decoder(index: i32) -> String {
-    unsafe {
-        let name = mixer::Mix_GetChunkDecoder(index as c_int);
-        from_utf8(CStr::from_ptr(name).to_bytes())
-            .unwrap()
-            .to_owned()
+    unsafe { decoder_name(mixer::Mix_GetChunkDecoder(index as c_int)) }
+}
+
+/// Converts a decoder name returned by `SDL2_mixer`, which is null for an out of range index.
+unsafe fn decoder_name(name: *const c_char) -> String {
+    if name.is_null() {
+        String::new()
+    } else {
+        CStr::from_ptr(name).to_string_lossy().into_owned()
     }
 }
 
@@ -253,8 +372,24 @@ impl Drop for Chunk {
 
 impl Chunk {
     /// Load file for use as a sample.
+    ///
+    /// The audio is converted to the format, sample rate and channels of the
+    /// opened device, so the audio must be open.
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Chunk, String> {
-        let raw = unsafe { mixer::Mix_LoadWAV_RW(RWops::from_file(path, "rb")?.raw(), 0) };
+        let rwops = RWops::from_file(path, "rb")?;
+        let raw = unsafe { mixer::Mix_LoadWAV_RW(rwops.raw(), 0) };
+        // the error is read before closing the RWops, which may replace it
+        Self::from_owned_raw(raw)
+    }
+
+    /// Load a sample from the data of the given reader, in any format supported by `from_file`.
+    ///
+    /// Reading fails if there is no data, or if there is more than `max_size` bytes, which
+    /// protects from endless streams.
+    pub fn from_reader<R: Read>(reader: R, max_size: Option<usize>) -> Result<Chunk, String> {
+        let data = read_to_end_limited(reader, max_size, "sound")?;
+        let rwops = RWops::from_bytes(&data)?;
+        let raw = unsafe { mixer::Mix_LoadWAV_RW(rwops.raw(), 0) };
         Self::from_owned_raw(raw)
     }
 
@@ -262,14 +397,77 @@ impl Chunk {
     /// buffer has to fit in 32-bit unsigned integer. The chunk takes ownership of the buffer.
     ///
     /// It's your responsibility to provide the audio data in the right format, as no conversion
-    /// will take place when using this method.
+    /// will take place when using this method. Use `from_raw_buffer_with_spec` for data in
+    /// another format or at another sample rate.
     pub fn from_raw_buffer<T: AudioFormatNum>(buffer: Box<[T]>) -> Result<Chunk, String> {
         use std::mem::size_of;
-        let len: u32 = (buffer.len() * size_of::<T>()).try_into().unwrap();
+        let len: u32 = (buffer.len() * size_of::<T>())
+            .try_into()
+            .map_err(|_| "Buffer too large for a chunk".to_owned())?;
         let raw = unsafe { mixer::Mix_QuickLoad_RAW(Box::into_raw(buffer) as *mut u8, len) };
         Self::from_owned_raw(raw)
     }
 
+    /// Load chunk from a buffer containing raw audio data like `from_raw_buffer`, checking that
+    /// its sample type, sample rate and number of channels are those of the opened device.
+    ///
+    /// Mismatched data would otherwise play garbled, or at the wrong speed and pitch, so it is
+    /// rejected with an error, as well as a buffer holding a partial frame. Use
+    /// `from_raw_buffer_with_spec` to convert it instead.
+    pub fn from_raw_buffer_checked<T: AudioFormatNum>(
+        buffer: Box<[T]>,
+        frequency: i32,
+        channels: i32,
+    ) -> Result<Chunk, String> {
+        let (dst_frequency, dst_format, dst_channels) = query_spec()?;
+        let format = T::audio_format() as AudioFormat;
+        if format != dst_format {
+            return Err(format!(
+                "Sample format 0x{:x} doesn't match the device format 0x{:x}",
+                format, dst_format
+            ));
+        }
+        if frequency != dst_frequency || channels != dst_channels {
+            return Err(format!(
+                "{} Hz with {} channels doesn't match the device, at {} Hz with {} channels",
+                frequency, channels, dst_frequency, dst_channels
+            ));
+        }
+        if channels <= 0 || !buffer.len().is_multiple_of(channels as usize) {
+            return Err(format!(
+                "{} samples don't make whole frames of {} channels",
+                buffer.len(),
+                channels
+            ));
+        }
+        Chunk::from_raw_buffer(buffer)
+    }
+
+    /// Load chunk from a copy of a buffer containing raw audio data with the given sample rate,
+    /// format and number of channels, converting it to the format of the opened device like
+    /// `from_file` does. Data that doesn't match the device would otherwise play at the wrong
+    /// speed and pitch.
+    pub fn from_raw_buffer_with_spec<T: AudioFormatNum>(
+        buffer: &[T],
+        frequency: i32,
+        format: AudioFormat,
+        channels: i32,
+    ) -> Result<Chunk, String> {
+        let (dst_frequency, dst_format, dst_channels) = query_spec()?;
+        let cvt = AudioCVT::new(
+            pcm::to_audio_format(format)?,
+            channels as u8,
+            frequency,
+            pcm::to_audio_format(dst_format)?,
+            dst_channels as u8,
+            dst_frequency,
+        )?;
+        let bytes = unsafe {
+            slice::from_raw_parts(buffer.as_ptr() as *const u8, mem::size_of_val(buffer))
+        };
+        Chunk::from_pcm(cvt.convert(bytes.to_vec()))
+    }
+
     fn from_owned_raw(raw: *mut mixer::Mix_Chunk) -> Result<Chunk, String> {
         if raw.is_null() {
             Err(get_error())
@@ -281,6 +479,18 @@ impl Chunk {
         }
     }
 
+    /// Returns the format, number of channels and sample rate of the audio data of the chunk,
+    /// or `None` if the audio isn't open.
+    ///
+    /// Chunks are stored in the format of the opened device, so this is the format given by
+    /// `query_spec`, which a chunk loaded before the device was reopened with another format
+    /// doesn't match anymore.
+    pub fn format(&self) -> Option<(AudioFormat, i32, i32)> {
+        query_spec()
+            .ok()
+            .map(|(frequency, format, channels)| (format, channels, frequency))
+    }
+
     /// Set chunk->volume to volume.
     pub fn set_volume(&mut self, volume: i32) -> i32 {
         unsafe { mixer::Mix_VolumeChunk(self.raw, volume as c_int) as i32 }
@@ -290,6 +500,173 @@ impl Chunk {
     pub fn get_volume(&self) -> i32 {
         unsafe { mixer::Mix_VolumeChunk(self.raw, -1) as i32 }
     }
+
+    /// Returns a copy of the chunk with its own audio data and volume.
+    ///
+    /// The same chunk can already be played on several channels at once, so
+    /// sharing it through an `Rc` is enough unless the copies need different
+    /// volumes.
+    pub fn try_clone(&self) -> Result<Chunk, String> {
+        let mut chunk = Chunk::from_pcm(self.pcm().to_vec())?;
+        chunk.set_volume(self.get_volume());
+        Ok(chunk)
+    }
+
+    /// Returns a copy of the chunk shifted by the given number of semitones,
+    /// by resampling its audio data. Raising the pitch also shortens the
+    /// sound, like playing a tape faster.
+    ///
+    /// The audio must be open, as chunks are stored in the format of the
+    /// opened device.
+    pub fn with_pitch(&self, semitones: f32) -> Result<Chunk, String> {
+        if !semitones.is_finite() {
+            return Err(format!("Invalid pitch shift: {}", semitones));
+        }
+        self.resampled(2f64.powf(f64::from(semitones) / 12.0))
+    }
+
+    /// Returns a copy of the chunk read `ratio` times faster.
+    fn resampled(&self, ratio: f64) -> Result<Chunk, String> {
+        let (_, format, channels) = query_spec()?;
+        let samples = pcm::decode(format, self.pcm())?;
+        let shifted = pcm::resample(&samples, channels.max(1) as usize, ratio);
+        let mut chunk = Chunk::from_pcm(pcm::encode(format, &shifted)?)?;
+        chunk.set_volume(self.get_volume());
+        Ok(chunk)
+    }
+
+    /// Returns a new chunk playing this chunk and `other` scaled by `gain`
+    /// at the same time, to layer sounds into a single one ahead of time.
+    ///
+    /// The new chunk is as long as the longer of the two. Samples are summed
+    /// in the format of the opened device and clipped to its range, so the
+    /// audio must be open. The new chunk has the volume of this chunk.
+    pub fn mix_with(&self, other: &Chunk, gain: f32) -> Result<Chunk, String> {
+        if !gain.is_finite() {
+            return Err(format!("Invalid gain: {}", gain));
+        }
+        let (_, format, _) = query_spec()?;
+        let samples = pcm::decode(format, self.pcm())?;
+        let other = pcm::decode(format, other.pcm())?;
+        let mut chunk = Chunk::from_pcm(pcm::encode(format, &pcm::mix(&samples, &other, gain))?)?;
+        chunk.set_volume(self.get_volume());
+        Ok(chunk)
+    }
+
+    /// Returns a copy of the chunk whose first `ms` milliseconds ramp up
+    /// linearly from silence, baking a fade-in into the sound itself rather
+    /// than fading the channel with `Channel::fade_in`.
+    ///
+    /// The audio must be open, as chunks are stored in the format of the
+    /// opened device. A fade longer than the chunk ramps up over the whole
+    /// chunk without reaching full amplitude.
+    pub fn with_fade_in(&self, ms: u32) -> Result<Chunk, String> {
+        let (frequency, format, channels) = query_spec()?;
+        let mut samples = pcm::decode(format, self.pcm())?;
+        let frames = (u64::from(ms) * frequency.max(0) as u64 / 1000) as usize;
+        pcm::fade_in(&mut samples, channels.max(1) as usize, frames);
+        let mut chunk = Chunk::from_pcm(pcm::encode(format, &samples)?)?;
+        chunk.set_volume(self.get_volume());
+        Ok(chunk)
+    }
+
+    /// Returns a copy of the chunk playing backwards, for rewind sounds.
+    ///
+    /// The audio must be open, as chunks are stored in the format of the
+    /// opened device, whose channels are kept in place.
+    pub fn reversed(&self) -> Result<Chunk, String> {
+        let (_, format, channels) = query_spec()?;
+        let frame_size = pcm::sample_size(format)? * channels.max(1) as usize;
+        let mut chunk = Chunk::from_pcm(pcm::reverse_frames(self.pcm(), frame_size))?;
+        chunk.set_volume(self.get_volume());
+        Ok(chunk)
+    }
+
+    /// Removes the silence at the beginning and at the end of the chunk,
+    /// that is the frames whose samples are all within `threshold` of zero,
+    /// on the scale of 16-bit samples whatever the format of the device.
+    ///
+    /// Like `update_buffer`, this requires the audio to be open and the
+    /// chunk to be owned and not playing. A chunk that is silent throughout
+    /// is left unchanged and an error is returned.
+    pub fn trim_silence(&mut self, threshold: i16) -> Result<(), String> {
+        let (_, format, channels) = query_spec()?;
+        let threshold = f32::from(threshold).abs() / 32_768.0;
+        let range = pcm::audible_range(format, channels.max(1) as usize, self.pcm(), threshold)?
+            .ok_or_else(|| "The chunk is silent".to_owned())?;
+        if range.len() == self.pcm().len() {
+            return Ok(());
+        }
+        let trimmed = self.pcm()[range].to_vec();
+        self.update_buffer(&trimmed)
+    }
+
+    /// Replaces the audio data of the chunk with a copy of `data`, raw audio
+    /// in the format of the opened device, for sounds generated again and
+    /// again. Data as long as the current one is copied in place, without
+    /// allocating.
+    ///
+    /// The length of `data` must be a whole number of sample frames. The
+    /// chunk must be owned, as returned by a loader rather than by
+    /// `Channel::get_chunk`, and must not be playing on any channel, since
+    /// channels keep reading the old data until they are done.
+    pub fn update_buffer(&mut self, data: &[u8]) -> Result<(), String> {
+        if !self.owned {
+            return Err("Can't update the buffer of a chunk that is not owned".to_owned());
+        }
+        let (_, format, channels) = query_spec()?;
+        let frame_size = pcm::sample_size(format)? * channels.max(1) as usize;
+        if !data.len().is_multiple_of(frame_size) {
+            return Err(format!(
+                "Buffer of {} bytes is not a whole number of {}-byte frames",
+                data.len(),
+                frame_size
+            ));
+        }
+        let len: u32 = data
+            .len()
+            .try_into()
+            .map_err(|_| "Buffer too large for a chunk".to_owned())?;
+        if self.is_playing() {
+            return Err("Can't update the buffer of a chunk that is playing".to_owned());
+        }
+
+        unsafe {
+            let raw = &mut *self.raw;
+            if raw.alen == len {
+                slice::from_raw_parts_mut(raw.abuf, data.len()).copy_from_slice(data);
+                return Ok(());
+            }
+            let old = raw.abuf;
+            let was_allocated = raw.allocated != 0;
+            raw.abuf = Box::into_raw(data.to_vec().into_boxed_slice()) as *mut u8;
+            raw.alen = len;
+            // the new buffer is freed on drop like the ones of `from_raw_buffer`
+            raw.allocated = 0;
+            if was_allocated {
+                sys::SDL_free(old as *mut c_void);
+            } else {
+                drop(Box::from_raw(old));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the chunk is playing on any of the allocated channels.
+    fn is_playing(&self) -> bool {
+        let channels = unsafe { mixer::Mix_AllocateChannels(-1) };
+        (0..channels)
+            .any(|ch| unsafe { mixer::Mix_Playing(ch) != 0 && mixer::Mix_GetChunk(ch) == self.raw })
+    }
+
+    /// The raw audio data of the chunk, in the format of the opened device.
+    fn pcm(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts((*self.raw).abuf, (*self.raw).alen as usize) }
+    }
+
+    fn from_pcm(data: Vec<u8>) -> Result<Chunk, String> {
+        Chunk::from_raw_buffer(data.into_boxed_slice())
+    }
 }
 
 /// Loader trait for `RWops`
@@ -323,6 +700,7 @@ impl<'a> LoaderRWops<'a> for RWops<'a> {
             Ok(Music {
                 raw: raw,
                 owned: true,
+                buffer: None,
                 _marker: PhantomData,
             })
         }
@@ -344,6 +722,37 @@ pub enum Fading {
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Channel(pub i32);
 
+/// An error that occured while starting playback on a channel.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlayError {
+    /// Every mixing channel is already playing.
+    NoFreeChannel,
+    /// A SDL2-related error occured.
+    SdlError(String),
+}
+
+impl fmt::Display for PlayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::PlayError::*;
+
+        match *self {
+            NoFreeChannel => write!(f, "No free channel available"),
+            SdlError(ref e) => write!(f, "SDL error: {}", e),
+        }
+    }
+}
+
+impl error::Error for PlayError {
+    fn description(&self) -> &str {
+        use self::PlayError::*;
+
+        match *self {
+            NoFreeChannel => "no free channel available",
+            SdlError(ref e) => e,
+        }
+    }
+}
+
 /// Set the number of channels being mixed.
 pub fn allocate_channels(numchans: i32) -> i32 {
     unsafe { mixer::Mix_AllocateChannels(numchans as c_int) as i32 }
@@ -351,19 +760,96 @@ pub fn allocate_channels(numchans: i32) -> i32 {
 
 static mut CHANNEL_FINISHED_CALLBACK: Option<fn(Channel)> = None;
 
+type ChannelFinishedFn = Box<dyn FnMut(Channel, Option<Chunk>) + Send>;
+
+#[derive(Default)]
+struct ChannelFinishedEx {
+    callback: Option<ChannelFinishedFn>,
+    // bumped whenever the function is set or unset, so that a function taken
+    // out to be called isn't put back once it has been replaced
+    generation: u64,
+}
+
+lazy_static! {
+    static ref CHANNEL_FINISHED_EX: Mutex<ChannelFinishedEx> =
+        Mutex::new(ChannelFinishedEx::default());
+}
+
+fn channel_finished_ex() -> MutexGuard<'static, ChannelFinishedEx> {
+    CHANNEL_FINISHED_EX
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+}
+
 extern "C" fn c_channel_finished_callback(ch: c_int) {
+    #[cfg(feature = "mixer-futures")]
+    future::channel_finished(ch);
     unsafe {
         match CHANNEL_FINISHED_CALLBACK {
             None => (),
             Some(cb) => cb(Channel(ch as i32)),
         }
     }
+    // the function is called without the lock, as it may halt channels or
+    // drop chunks, which calls this hook again
+    let (callback, generation) = {
+        let mut state = channel_finished_ex();
+        (state.callback.take(), state.generation)
+    };
+    if let Some(mut callback) = callback {
+        let channel = Channel(ch);
+        callback(channel, channel.get_chunk());
+        let replaced = {
+            let mut state = channel_finished_ex();
+            if state.generation == generation {
+                state.callback = Some(callback);
+                None
+            } else {
+                Some(callback)
+            }
+        };
+        drop(replaced);
+    }
+}
+
+/// Sets the function of `set_channel_finished_ex`, dropping the previous one
+/// outside of the lock, as dropping it may halt channels.
+fn replace_channel_finished_ex(callback: Option<ChannelFinishedFn>) {
+    let previous = {
+        let mut state = channel_finished_ex();
+        state.generation = state.generation.wrapping_add(1);
+        mem::replace(&mut state.callback, callback)
+    };
+    drop(previous);
 }
 
 /// When channel playback is halted, then the specified `channel_finished` function is called.
 pub fn set_channel_finished(f: fn(Channel)) {
     unsafe {
         CHANNEL_FINISHED_CALLBACK = Some(f);
+    }
+    set_channel_finished_hook();
+}
+
+/// When channel playback is halted, then the specified function is called with the channel and
+/// the chunk that was playing on it, as found by `Channel::get_chunk`, for bookkeeping of pooled
+/// sounds. It is called after the function set with `set_channel_finished`, which it doesn't
+/// replace.
+///
+/// The chunk doesn't own its audio data, which belongs to the chunk that was played; channels
+/// playing at another speed than 1.0 played a resampled copy, which is what is passed. The
+/// function is called from the audio thread, or from the thread halting the channel, and should
+/// not play or halt channels itself. It isn't called again for channels finishing while it runs.
+pub fn set_channel_finished_ex<F>(f: F)
+where
+    F: FnMut(Channel, Option<Chunk>) + Send + 'static,
+{
+    replace_channel_finished_ex(Some(Box::new(f)));
+    set_channel_finished_hook();
+}
+
+fn set_channel_finished_hook() {
+    unsafe {
         mixer::Mix_ChannelFinished(Some(
             c_channel_finished_callback as extern "C" fn(ch: c_int),
         ));
@@ -371,12 +857,20 @@ pub fn set_channel_finished(f: fn(Channel)) {
 }
 
 /// Unhooks the specified function set before, so no function is called when channel playback is
-/// halted.
+/// halted. This unhooks the function set with `set_channel_finished_ex` as well.
 pub fn unset_channel_finished() {
+    // the hook stays set while futures wait on channels
+    #[cfg(feature = "mixer-futures")]
+    let keep_hook = future::has_waiting();
+    #[cfg(not(feature = "mixer-futures"))]
+    let keep_hook = false;
     unsafe {
-        mixer::Mix_ChannelFinished(None);
+        if !keep_hook {
+            mixer::Mix_ChannelFinished(None);
+        }
         CHANNEL_FINISHED_CALLBACK = None;
     }
+    replace_channel_finished_ex(None);
 }
 
 impl Channel {
@@ -402,23 +896,98 @@ impl Channel {
         unsafe { mixer::Mix_Volume(ch as c_int, -1) as i32 }
     }
 
+    /// Set the volume for any allocated channel as a gain in decibels.
+    ///
+    /// 0 dB maps to `MAX_VOLUME` and every -6 dB roughly halves the volume.
+    /// Since SDL_mixer can't amplify, positive gains are clamped to
+    /// `MAX_VOLUME`, and gains too low to be represented end up silent.
+    /// Returns the previous volume, like `set_volume`.
+    pub fn set_gain_db(self, db: f32) -> i32 {
+        let volume = MAX_VOLUME as f32 * 10f32.powf(db / 20.0);
+        self.set_volume((volume.round() as i32).clamp(0, MAX_VOLUME))
+    }
+
     /// Play chunk on channel, or if channel is -1, pick the first free unreserved channel.
     pub fn play(self, chunk: &Chunk, loops: i32) -> Result<Channel, String> {
         self.play_timed(chunk, loops, -1)
     }
 
+    /// Plays the chunk like `play`, returning a future resolving to the
+    /// channel once the chunk finishes or is halted, instead of polling
+    /// `is_playing`.
+    ///
+    /// The future is completed by the channel finished hook of SDL2_mixer,
+    /// which calls the function given to `set_channel_finished` as well.
+    /// The chunk must outlive its playback, like with `play`.
+    #[cfg(feature = "mixer-futures")]
+    pub fn play_future(self, chunk: &Chunk, loops: i32) -> Result<PlayFuture, String> {
+        set_channel_finished_hook();
+        let channel = self.play(chunk, loops)?;
+        Ok(future::wait(channel))
+    }
+
     pub fn play_timed(self, chunk: &Chunk, loops: i32, ticks: i32) -> Result<Channel, String> {
         let Channel(ch) = self;
+        let resampled = self.resampled(chunk)?;
+        let raw = resampled.as_ref().map_or(chunk.raw, |chunk| chunk.raw);
         let ret = unsafe {
-            mixer::Mix_PlayChannelTimed(ch as c_int, chunk.raw, loops as c_int, ticks as c_int)
+            mixer::Mix_PlayChannelTimed(ch as c_int, raw, loops as c_int, ticks as c_int)
         };
         if ret == -1 {
             Err(get_error())
         } else {
+            speed::set_playing(ret, resampled);
             Ok(Channel(ret as i32))
         }
     }
 
+    /// Plays the chunk at the given speed factor from now on: 2.0 plays it
+    /// twice as fast, an octave higher and in half the time, like a tape.
+    /// This is not time-stretching, which would keep the pitch.
+    ///
+    /// The speed applies to the chunks played or faded in afterwards on
+    /// this channel, which are resampled every time they start, so it can't
+    /// be set on `Channel::all()`. A factor of 1.0 plays chunks as they are.
+    pub fn set_speed(self, factor: f32) -> Result<(), String> {
+        let Channel(ch) = self;
+        if ch < 0 {
+            return Err(format!("Can't set the speed of channel {}", ch));
+        }
+        if !(factor.is_finite() && factor > 0.0) {
+            return Err(format!("Invalid speed: {}", factor));
+        }
+        speed::set_speed(ch as c_int, Some(factor).filter(|&factor| factor != 1.0));
+        Ok(())
+    }
+
+    /// Returns the speed factor set with `set_speed`, 1.0 by default.
+    pub fn speed(self) -> f32 {
+        let Channel(ch) = self;
+        speed::speed(ch as c_int).unwrap_or(1.0)
+    }
+
+    /// Returns the copy of the chunk to play at the speed of the channel,
+    /// if it has one.
+    fn resampled(self, chunk: &Chunk) -> Result<Option<Chunk>, String> {
+        let Channel(ch) = self;
+        match speed::speed(ch as c_int) {
+            Some(factor) => chunk.resampled(f64::from(factor)).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Play chunk on the first channel that is not currently playing.
+    ///
+    /// Unlike `Channel::all().play(...)`, this reports an explicit
+    /// `PlayError::NoFreeChannel` when every channel is busy. Note that
+    /// reserved channels are considered free as well.
+    pub fn play_on_free(chunk: &Chunk, loops: i32) -> Result<Channel, PlayError> {
+        match Group::default().find_available() {
+            None => Err(PlayError::NoFreeChannel),
+            Some(channel) => channel.play(chunk, loops).map_err(PlayError::SdlError),
+        }
+    }
+
     /// Play chunk on channel, or if channel is -1, pick the first free unreserved channel.
     pub fn fade_in(self, chunk: &Chunk, loops: i32, ms: i32) -> Result<Channel, String> {
         self.fade_in_timed(chunk, loops, ms, -1)
@@ -432,10 +1001,12 @@ impl Channel {
         ticks: i32,
     ) -> Result<Channel, String> {
         let Channel(ch) = self;
+        let resampled = self.resampled(chunk)?;
+        let raw = resampled.as_ref().map_or(chunk.raw, |chunk| chunk.raw);
         let ret = unsafe {
             mixer::Mix_FadeInChannelTimed(
                 ch as c_int,
-                chunk.raw,
+                raw,
                 loops as c_int,
                 ms as c_int,
                 ticks as c_int,
@@ -444,6 +1015,7 @@ impl Channel {
         if ret == -1 {
             Err(get_error())
         } else {
+            speed::set_playing(ret, resampled);
             Ok(Channel(ret as i32))
         }
     }
@@ -472,6 +1044,19 @@ impl Channel {
         }
     }
 
+    /// Halt channel playback with a fade-out of `ms` milliseconds, like
+    /// `DEFAULT_HALT_RAMP_MS`, to avoid the click of cutting the sound
+    /// mid-waveform. The channel stops playing once the fade-out is over.
+    ///
+    /// A paused channel can't fade out, so it is halted right away.
+    pub fn halt_smooth(self, ms: u32) {
+        if ms == 0 || self.is_paused() {
+            self.halt();
+        } else {
+            self.fade_out(ms.min(i32::MAX as u32) as i32);
+        }
+    }
+
     /// Halt channel playback, after ticks milliseconds.
     pub fn expire(self, ticks: i32) -> i32 {
         let Channel(ch) = self;
@@ -521,6 +1106,20 @@ impl Channel {
         }
     }
 
+    /// Returns the chunk the channel is playing, or `None` if it is not
+    /// playing anything. Unlike `get_chunk`, a chunk that finished playing
+    /// is not returned.
+    ///
+    /// The returned chunk does not own the sound data: dropping it does not
+    /// free the chunk, which remains owned by whoever loaded it.
+    pub fn playing_chunk(self) -> Option<Chunk> {
+        if self.is_playing() {
+            self.get_chunk()
+        } else {
+            None
+        }
+    }
+
     /// This removes all effects registered to channel.
     pub fn unregister_all_effects(self) -> Result<(), String> {
         let Channel(ch) = self;
@@ -540,10 +1139,22 @@ impl Channel {
         if ret == 0 {
             Err(get_error())
         } else {
+            effects::set_registered(ch as c_int, EffectId::Panning, (left, right) != (255, 255));
             Ok(())
         }
     }
 
+    /// Sets the volume of the left and right speakers on the scale of
+    /// `set_volume`, from 0 to `MAX_VOLUME`, for simple stereo placement.
+    ///
+    /// This is a panning effect, see `set_panning`: it scales the output of
+    /// the channel, so the volume set with `set_volume` still applies on top
+    /// of it. Volumes out of range are clamped.
+    pub fn set_stereo_volume(self, left: i32, right: i32) -> Result<(), String> {
+        let to_panning = |volume: i32| (volume.clamp(0, MAX_VOLUME) * 255 / MAX_VOLUME) as u8;
+        self.set_panning(to_panning(left), to_panning(right))
+    }
+
     /// Unregisters panning effect.
     pub fn unset_panning(self) -> Result<(), String> {
         let Channel(ch) = self;
@@ -551,6 +1162,7 @@ impl Channel {
         if ret == 0 {
             Err(get_error())
         } else {
+            effects::set_registered(ch as c_int, EffectId::Panning, false);
             Ok(())
         }
     }
@@ -563,6 +1175,7 @@ impl Channel {
         if ret == 0 {
             Err(get_error())
         } else {
+            effects::set_registered(ch as c_int, EffectId::Distance, distance != 0);
             Ok(())
         }
     }
@@ -574,6 +1187,7 @@ impl Channel {
         if ret == 0 {
             Err(get_error())
         } else {
+            effects::set_registered(ch as c_int, EffectId::Distance, false);
             Ok(())
         }
     }
@@ -587,6 +1201,8 @@ impl Channel {
         if ret == 0 {
             Err(get_error())
         } else {
+            effects::set_registered(ch as c_int, EffectId::Position, angle % 360 != 0);
+            effects::set_registered(ch as c_int, EffectId::Distance, distance != 0);
             Ok(())
         }
     }
@@ -598,6 +1214,8 @@ impl Channel {
         if ret == 0 {
             Err(get_error())
         } else {
+            effects::set_registered(ch as c_int, EffectId::Position, false);
+            effects::set_registered(ch as c_int, EffectId::Distance, false);
             Ok(())
         }
     }
@@ -610,6 +1228,7 @@ impl Channel {
         if ret == 0 {
             Err(get_error())
         } else {
+            effects::set_registered(ch as c_int, EffectId::ReverseStereo, flip);
             Ok(())
         }
     }
@@ -625,12 +1244,71 @@ pub fn get_paused_channels_number() -> i32 {
     unsafe { mixer::Mix_Paused(-1) as i32 }
 }
 
+/// Plays each chunk on its channel the given number of loops, like
+/// `Channel::play`, to start several sounds in one call.
+///
+/// Every chunk is started even if some fail, in which case the failures are
+/// returned together, one per line.
+pub fn play_all(plays: &[(Channel, &Chunk, i32)]) -> Result<(), String> {
+    let failures: Vec<String> = plays
+        .iter()
+        .filter_map(|&(channel, chunk, loops)| {
+            channel
+                .play(chunk, loops)
+                .err()
+                .map(|err| format!("Channel {}: {}", channel.0, err))
+        })
+        .collect();
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(failures.join("\n"))
+    }
+}
+
+/// Blocks until none of the given channels is playing, checking every
+/// `poll_ms` milliseconds.
+///
+/// Paused channels count as playing, so waiting on them only returns once
+/// they are resumed and done, or halted.
+pub fn wait_for_channels(channels: &[Channel], poll_ms: u32) {
+    while channels.iter().any(|channel| channel.is_playing()) {
+        thread::sleep(Duration::from_millis(u64::from(poll_ms)));
+    }
+}
+
+/// Like `wait_for_channels`, but gives up after `timeout_ms` milliseconds.
+/// Returns `true` if it timed out while a channel was still playing.
+pub fn wait_for_channels_timeout(channels: &[Channel], poll_ms: u32, timeout_ms: u32) -> bool {
+    let deadline = Instant::now() + Duration::from_millis(u64::from(timeout_ms));
+    while channels.iter().any(|channel| channel.is_playing()) {
+        let now = Instant::now();
+        if now >= deadline {
+            return true;
+        }
+        thread::sleep((deadline - now).min(Duration::from_millis(u64::from(poll_ms))));
+    }
+    false
+}
+
 // 4.4 Groups
 
+// The number of channels reserved by the last call to `reserve_channels`,
+// which SDL2_mixer can't report.
+static RESERVED_CHANNELS: AtomicI32 = AtomicI32::new(0);
+
 /// Reserve num channels from being used when playing samples when
 /// passing in -1 as a channel number to playback functions.
 pub fn reserve_channels(num: i32) -> i32 {
-    unsafe { mixer::Mix_ReserveChannels(num as c_int) as i32 }
+    let reserved = unsafe { mixer::Mix_ReserveChannels(num as c_int) as i32 };
+    RESERVED_CHANNELS.store(reserved, Ordering::Relaxed);
+    reserved
+}
+
+/// Returns the number of channels reserved by the last call to
+/// `reserve_channels`, that is channels 0 up to that number excluded.
+pub fn reserved_channels() -> i32 {
+    RESERVED_CHANNELS.load(Ordering::Relaxed)
 }
 
 /// Sound effect channel grouping.
@@ -719,16 +1397,49 @@ pub fn get_music_decoders_number() -> i32 {
     unsafe { mixer::Mix_GetNumMusicDecoders() as i32 }
 }
 
-/// Get the name of the indexed music decoder.
+/// Get the name of the indexed music decoder, or an empty string if the index is out of range.
 pub fn get_music_decoder(index: i32) -> String {
+    unsafe { decoder_name(mixer::Mix_GetMusicDecoder(index as c_int)) }
+}
+
+/// Sets the SoundFont files used by the FluidSynth backend to play MIDI
+/// music, as a list of paths separated by `;`.
+pub fn set_soundfonts(paths: &str) -> Result<(), String> {
+    let c_paths = CString::new(paths).map_err(|err| err.to_string())?;
+    let ret = unsafe { mixer::Mix_SetSoundFonts(c_paths.as_ptr()) };
+    if ret == 0 {
+        Err(get_error())
+    } else {
+        Ok(())
+    }
+}
+
+/// Returns the SoundFont files used to play MIDI music, as set with
+/// `set_soundfonts` or through the `SDL_SOUNDFONTS` environment variable.
+pub fn get_soundfonts() -> Option<String> {
     unsafe {
-        let name = mixer::Mix_GetMusicDecoder(index as c_int);
-        from_utf8(CStr::from_ptr(name).to_bytes())
-            .unwrap()
-            .to_owned()
+        let paths = mixer::Mix_GetSoundFonts();
+        if paths.is_null() {
+            None
+        } else {
+            Some(CStr::from_ptr(paths).to_string_lossy().into_owned())
+        }
     }
 }
 
+/// Sets the configuration file used by the Timidity backend to play MIDI
+/// music.
+///
+/// `SDL2_mixer` only reads it from the `TIMIDITY_CFG` environment variable,
+/// which this sets, when Timidity is first used: this must be called before
+/// opening the audio.
+pub fn set_timidity_config<P: AsRef<Path>>(path: P) -> Result<(), String> {
+    // the environment can't hold nul characters either
+    path_to_cstring(path.as_ref())?;
+    env::set_var("TIMIDITY_CFG", path.as_ref());
+    Ok(())
+}
+
 /// Music type enumerations
 #[repr(i32)]
 #[derive(Copy, Clone, PartialEq, Hash, Debug)]
@@ -762,6 +1473,8 @@ extern "C" fn c_music_finished_hook() {
 pub struct Music<'a> {
     pub raw: *mut mixer::Mix_Music,
     pub owned: bool,
+    // Data the music is streamed from, for music read from a reader
+    buffer: Option<Box<[u8]>>,
     _marker: PhantomData<&'a ()>,
 }
 
@@ -784,7 +1497,7 @@ impl<'a> Music<'a> {
     /// Load music file to use.
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Music<'static>, String> {
         let raw = unsafe {
-            let c_path = CString::new(path.as_ref().to_str().unwrap()).unwrap();
+            let c_path = path_to_cstring(path.as_ref())?;
             mixer::Mix_LoadMUS(c_path.as_ptr())
         };
         if raw.is_null() {
@@ -793,6 +1506,7 @@ impl<'a> Music<'a> {
             Ok(Music {
                 raw: raw,
                 owned: true,
+                buffer: None,
                 _marker: PhantomData,
             })
         }
@@ -815,6 +1529,62 @@ impl<'a> Music<'a> {
             Ok(Music {
                 raw: raw,
                 owned: true,
+                buffer: None,
+                _marker: PhantomData,
+            })
+        }
+    }
+
+    /// Load music from the data of the given reader.
+    ///
+    /// The data is read whole and kept in memory, as music is decoded while
+    /// playing. Reading fails if there is no data, or if there is more than
+    /// `max_size` bytes, which protects from endless streams.
+    pub fn from_reader<R: Read>(
+        reader: R,
+        max_size: Option<usize>,
+    ) -> Result<Music<'static>, String> {
+        let buffer = read_to_end_limited(reader, max_size, "music")?.into_boxed_slice();
+        let rwops = RWops::from_bytes(&buffer)?;
+        let raw = unsafe { mixer::Mix_LoadMUS_RW(rwops.raw(), 1) };
+        mem::forget(rwops);
+        let mut music = Music::from_owned_raw(raw)?;
+        music.buffer = Some(buffer);
+        Ok(music)
+    }
+
+    /// Load music from a byte buffer, which must outlive the returned `Music`.
+    #[doc(alias = "Mix_LoadMUS_RW")]
+    pub fn from_bytes(buf: &'a [u8]) -> Result<Music<'a>, String> {
+        let rwops = RWops::from_bytes(buf)?;
+        // SDL_mixer takes ownership of the RWops and closes it along with the music
+        let raw = unsafe { mixer::Mix_LoadMUS_RW(rwops.raw(), 1) };
+        mem::forget(rwops);
+        Music::from_owned_raw(raw)
+    }
+
+    /// Load music of the given type from a byte buffer, which must outlive
+    /// the returned `Music`.
+    ///
+    /// This is useful for streams whose format can't be detected reliably.
+    #[doc(alias = "Mix_LoadMUSType_RW")]
+    pub fn from_bytes_with_type(buf: &'a [u8], music_type: MusicType) -> Result<Music<'a>, String> {
+        let rwops = RWops::from_bytes(buf)?;
+        let raw = unsafe {
+            mixer::Mix_LoadMUSType_RW(rwops.raw(), music_type as mixer::Mix_MusicType, 1)
+        };
+        mem::forget(rwops);
+        Music::from_owned_raw(raw)
+    }
+
+    fn from_owned_raw(raw: *mut mixer::Mix_Music) -> Result<Music<'a>, String> {
+        if raw.is_null() {
+            Err(get_error())
+        } else {
+            Ok(Music {
+                raw,
+                owned: true,
+                buffer: None,
                 _marker: PhantomData,
             })
         }
@@ -843,6 +1613,7 @@ impl<'a> Music<'a> {
         if ret == -1 {
             Err(get_error())
         } else {
+            music_clock::started(loops, 0.0);
             Ok(())
         }
     }
@@ -854,6 +1625,7 @@ impl<'a> Music<'a> {
         if ret == -1 {
             Err(get_error())
         } else {
+            music_clock::started(loops, 0.0);
             Ok(())
         }
     }
@@ -866,10 +1638,62 @@ impl<'a> Music<'a> {
         if ret == -1 {
             Err(get_error())
         } else {
+            music_clock::started(loops, position);
             Ok(())
         }
     }
 
+    /// Plays this music from the position the music was at when the audio
+    /// device was last reopened with `reopen_audio`, looping as many times as
+    /// it was started with, and paused if it was.
+    ///
+    /// This music should be the one that was playing, loaded again if the
+    /// format of the device changed. The position is estimated from the time
+    /// the music has been playing, which is only right during the first time
+    /// through looping music. Returns an error if no music was playing then,
+    /// or if the format of this music can't seek, in which case it doesn't
+    /// play. The position is only resumed once.
+    pub fn resume_at_last_position(&self) -> Result<(), String> {
+        let saved = music_clock::saved()
+            .ok_or_else(|| "No music was playing when the audio was reopened".to_owned())?;
+        self.fade_in_from_pos(saved.loops, 0, saved.position)?;
+        if saved.paused {
+            Music::pause();
+        }
+        music_clock::forget_saved();
+        Ok(())
+    }
+
+    /// Returns the position of the playing music in seconds, or `None` if
+    /// no music is playing.
+    ///
+    /// `SDL2_mixer` can't tell the position, so it is estimated from the
+    /// time since the music started, not counting pauses, and the positions
+    /// it was moved to. It isn't wrapped when looping music starts over.
+    pub fn position() -> Option<f64> {
+        if Music::is_playing() {
+            music_clock::position()
+        } else {
+            None
+        }
+    }
+
+    /// Switch from the currently playing music to this one, fading over ms
+    /// milliseconds of time in total.
+    ///
+    /// `SDL2_mixer` only plays one music stream at a time, so the two tracks
+    /// can't overlap: the current music fades out during the first half of
+    /// the time and this one fades in during the second half. The call blocks
+    /// until the fade-out is over, as `SDL2_mixer` waits for it before
+    /// starting the new music.
+    pub fn crossfade(&self, loops: i32, ms: i32) -> Result<(), String> {
+        let fade_out_ms = ms / 2;
+        if fade_out_ms > 0 && Music::is_playing() && !Music::is_paused() {
+            Music::fade_out(fade_out_ms)?;
+        }
+        self.fade_in(loops, ms - fade_out_ms)
+    }
+
     // FIXME: make these class method?
     /// Returns current volume
     pub fn get_volume() -> i32 {
@@ -888,6 +1712,7 @@ impl<'a> Music<'a> {
         unsafe {
             mixer::Mix_PauseMusic();
         }
+        music_clock::paused();
     }
 
     /// Unpause the music.
@@ -895,6 +1720,7 @@ impl<'a> Music<'a> {
         unsafe {
             mixer::Mix_ResumeMusic();
         }
+        music_clock::resumed();
     }
 
     /// Rewind the music to the start.
@@ -902,6 +1728,7 @@ impl<'a> Music<'a> {
         unsafe {
             mixer::Mix_RewindMusic();
         }
+        music_clock::moved(0.0);
     }
 
     /// Set the position of the currently playing music.
@@ -910,6 +1737,7 @@ impl<'a> Music<'a> {
         if ret == -1 {
             Err(get_error())
         } else {
+            music_clock::moved(position);
             Ok(())
         }
     }
@@ -917,7 +1745,7 @@ impl<'a> Music<'a> {
     /// Setup a command line music player to use to play music.
     pub fn set_command(command: &str) -> Result<(), String> {
         let ret = unsafe {
-            let c_command = CString::new(command).unwrap();
+            let c_command = CString::new(command).map_err(|err| err.to_string())?;
             mixer::Mix_SetMusicCMD(c_command.as_ptr())
         };
         if ret == -1 {
@@ -932,6 +1760,7 @@ impl<'a> Music<'a> {
         unsafe {
             mixer::Mix_HaltMusic();
         }
+        music_clock::halted();
     }
 
     /// Gradually fade out the music over ms milliseconds starting from now.
@@ -997,6 +1826,192 @@ impl<'a> Music<'a> {
 
 // 4.6 Effects
 
-// TODO: Mix_RegisterEffect
-// TODO: Mix_UnregisterEffect
-// TODO: Mix_SetPostMix
+unsafe extern "C" fn c_effect_callback<F>(
+    _chan: c_int,
+    stream: *mut c_void,
+    len: c_int,
+    udata: *mut c_void,
+) where
+    F: FnMut(&mut [u8]),
+{
+    let f = &mut *(udata as *mut F);
+    f(slice::from_raw_parts_mut(stream as *mut u8, len as usize));
+}
+
+unsafe extern "C" fn c_effect_done<F>(chan: c_int, udata: *mut c_void) {
+    // The effect was unregistered (or the audio device closed), the boxed
+    // closure is no longer referenced by SDL_mixer.
+    effects::set_registered(chan, EffectId::Custom(udata as usize), false);
+    drop(Box::from_raw(udata as *mut F));
+}
+
+impl Channel {
+    /// Registers an effect function on the channel, or on the final mixed
+    /// stream if this is `Channel::post()`.
+    ///
+    /// The function is called from the audio thread with the channel's
+    /// audio data, in the format returned by `query_spec`, which it may
+    /// modify in place. Effects are run in the order they were registered and
+    /// are freed when unregistered with `unregister_all_effects` or when the
+    /// audio device is closed.
+    pub fn register_effect<F>(self, f: F) -> Result<(), String>
+    where
+        F: FnMut(&mut [u8]) + Send + 'static,
+    {
+        let Channel(ch) = self;
+        let udata = Box::into_raw(Box::new(f));
+        // tracked first, as the effect may be done before this returns
+        let effect = EffectId::Custom(udata as usize);
+        effects::set_registered(ch as c_int, effect, true);
+        let ret = unsafe {
+            mixer::Mix_RegisterEffect(
+                ch as c_int,
+                Some(c_effect_callback::<F>),
+                Some(c_effect_done::<F>),
+                udata as *mut c_void,
+            )
+        };
+        if ret == 0 {
+            let err = get_error();
+            effects::set_registered(ch as c_int, effect, false);
+            unsafe { drop(Box::from_raw(udata)) };
+            Err(err)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns whether any effect is registered on the channel, be it with
+    /// `register_effect`, `tap`, `set_lowpass` or one of the built-in
+    /// effects like `set_panning`.
+    ///
+    /// `SDL2_mixer` can't be asked this, so effects are tracked as they are
+    /// registered and removed through this crate, including when
+    /// `SDL2_mixer` removes them because the channel finished playing.
+    /// Effects registered through `sdl2::sys` directly are not seen.
+    pub fn has_effects(self) -> bool {
+        let Channel(ch) = self;
+        effects::has_effects(ch as c_int)
+    }
+
+    /// Registers a read-only effect on the channel, which receives the
+    /// audio data being played without being able to modify it.
+    ///
+    /// This is meant for recording or visualizing the output of a channel;
+    /// the data is in the format returned by `query_spec`.
+    pub fn tap<F>(self, mut f: F) -> Result<(), String>
+    where
+        F: FnMut(&[u8]) + Send + 'static,
+    {
+        self.register_effect(move |stream: &mut [u8]| f(stream))
+    }
+
+    /// Sets a low-pass filter on the channel, or on the final mixed stream if
+    /// this is `Channel::post()`, muffling the sound above `cutoff_hz`.
+    ///
+    /// The filter is a one-pole IIR filter, so it rolls off gently. Setting
+    /// it again replaces the previous cutoff. The audio must be open.
+    pub fn set_lowpass(self, cutoff_hz: f32) -> Result<(), String> {
+        let (frequency, format, channels) = query_spec()?;
+        // fails on output formats the filter can't process
+        pcm::sample_size(format)?;
+        // there may be no filter to replace
+        let _ = self.unset_lowpass();
+        let Channel(ch) = self;
+        let filter = LowPassEffect {
+            format,
+            filter: pcm::LowPass::new(cutoff_hz, frequency, channels as usize),
+        };
+        let udata = Box::into_raw(Box::new(filter));
+        let effect = EffectId::Custom(udata as usize);
+        effects::set_registered(ch as c_int, effect, true);
+        let ret = unsafe {
+            mixer::Mix_RegisterEffect(
+                ch as c_int,
+                Some(c_lowpass_effect),
+                Some(c_effect_done::<LowPassEffect>),
+                udata as *mut c_void,
+            )
+        };
+        if ret == 0 {
+            let err = get_error();
+            effects::set_registered(ch as c_int, effect, false);
+            unsafe { drop(Box::from_raw(udata)) };
+            Err(err)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Removes the low-pass filter set with `set_lowpass`. Fails if there is
+    /// none.
+    pub fn unset_lowpass(self) -> Result<(), String> {
+        let Channel(ch) = self;
+        let ret = unsafe { mixer::Mix_UnregisterEffect(ch as c_int, Some(c_lowpass_effect)) };
+        if ret == 0 {
+            Err(get_error())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+struct LowPassEffect {
+    format: AudioFormat,
+    filter: pcm::LowPass,
+}
+
+unsafe extern "C" fn c_lowpass_effect(
+    _chan: c_int,
+    stream: *mut c_void,
+    len: c_int,
+    udata: *mut c_void,
+) {
+    let effect = &mut *(udata as *mut LowPassEffect);
+    let _ = effect.filter.process(
+        effect.format,
+        slice::from_raw_parts_mut(stream as *mut u8, len as usize),
+    );
+}
+
+// The master gain, stored as the bits of an `f32` so that the post-mix
+// callback can read it from the audio thread. Starts at 1.0.
+static MASTER_GAIN: AtomicU32 = AtomicU32::new(0x3f80_0000);
+
+unsafe extern "C" fn c_master_gain_post_mix(udata: *mut c_void, stream: *mut u8, len: c_int) {
+    let gain = f32::from_bits(MASTER_GAIN.load(Ordering::Relaxed));
+    if gain < 1.0 {
+        // the output format is passed as the user data
+        let format = udata as usize as AudioFormat;
+        let _ = pcm::scale(
+            format,
+            slice::from_raw_parts_mut(stream, len as usize),
+            gain,
+        );
+    }
+}
+
+/// Sets a gain applied to the whole mixed output, on top of the volumes of
+/// the channels and of the music, for a master volume control.
+///
+/// The gain is clamped between 0.0 (silence) and 1.0 (unchanged). It is
+/// applied by a post-mix callback, which replaces any other `Mix_SetPostMix`
+/// callback. The audio must be open.
+pub fn set_master_gain(gain: f32) -> Result<(), String> {
+    let (_, format, _) = query_spec()?;
+    // fails on output formats the callback can't scale
+    pcm::sample_size(format)?;
+    let gain = if gain.is_nan() {
+        0.0
+    } else {
+        gain.clamp(0.0, 1.0)
+    };
+    MASTER_GAIN.store(gain.to_bits(), Ordering::Relaxed);
+    unsafe { mixer::Mix_SetPostMix(Some(c_master_gain_post_mix), format as usize as *mut c_void) };
+    Ok(())
+}
+
+/// Returns the gain set with `set_master_gain`.
+pub fn master_gain() -> f32 {
+    f32::from_bits(MASTER_GAIN.load(Ordering::Relaxed))
+}
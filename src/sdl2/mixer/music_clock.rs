@@ -0,0 +1,112 @@
+//! The position of the music, estimated from the time it has been playing,
+//! as `SDL2_mixer` can set the position of the music but can't tell it.
+
+use std::sync::{Mutex, MutexGuard};
+use std::time::Instant;
+
+struct MusicClock {
+    loops: i32,
+    // the position when the music was last started, moved or resumed
+    position: f64,
+    // when the music was last started, moved or resumed, unless paused
+    since: Option<Instant>,
+}
+
+impl MusicClock {
+    fn position(&self) -> f64 {
+        self.position
+            + self
+                .since
+                .map_or(0.0, |since| since.elapsed().as_secs_f64())
+    }
+}
+
+/// The position the music was at when the audio device was reopened.
+#[derive(Clone, Copy)]
+pub struct SavedPosition {
+    pub position: f64,
+    pub loops: i32,
+    pub paused: bool,
+}
+
+#[derive(Default)]
+struct MusicState {
+    clock: Option<MusicClock>,
+    saved: Option<SavedPosition>,
+}
+
+lazy_static! {
+    static ref MUSIC: Mutex<MusicState> = Mutex::new(MusicState::default());
+}
+
+fn state() -> MutexGuard<'static, MusicState> {
+    MUSIC.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Starts the clock when the music starts playing, `loops` times from
+/// `position`.
+pub fn started(loops: i32, position: f64) {
+    state().clock = Some(MusicClock {
+        loops,
+        position,
+        since: Some(Instant::now()),
+    });
+}
+
+/// Restarts the clock from `position` when the music is moved there.
+pub fn moved(position: f64) {
+    if let Some(ref mut clock) = state().clock {
+        clock.position = position;
+        if clock.since.is_some() {
+            clock.since = Some(Instant::now());
+        }
+    }
+}
+
+pub fn paused() {
+    if let Some(ref mut clock) = state().clock {
+        if clock.since.is_some() {
+            clock.position = clock.position();
+            clock.since = None;
+        }
+    }
+}
+
+pub fn resumed() {
+    if let Some(ref mut clock) = state().clock {
+        if clock.since.is_none() {
+            clock.since = Some(Instant::now());
+        }
+    }
+}
+
+pub fn halted() {
+    state().clock = None;
+}
+
+/// Returns the estimated position of the music, if it was started since it
+/// was last halted.
+pub fn position() -> Option<f64> {
+    state().clock.as_ref().map(MusicClock::position)
+}
+
+/// Saves the position of the music, `None` if it isn't playing.
+pub fn save(playing: bool) {
+    let mut state = state();
+    state.saved = match state.clock {
+        Some(ref clock) if playing => Some(SavedPosition {
+            position: clock.position(),
+            loops: clock.loops,
+            paused: clock.since.is_none(),
+        }),
+        _ => None,
+    };
+}
+
+pub fn saved() -> Option<SavedPosition> {
+    state().saved
+}
+
+pub fn forget_saved() {
+    state().saved = None;
+}
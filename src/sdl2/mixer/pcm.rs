@@ -0,0 +1,321 @@
+//! Internal helpers to decode, process and encode the PCM data of chunks.
+//!
+//! Samples are converted to `f32` in the `-1.0..=1.0` range for processing,
+//! whatever the mixer format is.
+
+use audio;
+use std::ops::Range;
+
+use super::{
+    AudioFormat, AUDIO_F32LSB, AUDIO_F32MSB, AUDIO_S16LSB, AUDIO_S16MSB, AUDIO_S32LSB,
+    AUDIO_S32MSB, AUDIO_S8, AUDIO_U16LSB, AUDIO_U16MSB, AUDIO_U8,
+};
+
+/// Converts a mixer audio format to the equivalent `audio::AudioFormat`.
+pub fn to_audio_format(format: AudioFormat) -> Result<audio::AudioFormat, String> {
+    match format {
+        AUDIO_U8 => Ok(audio::AudioFormat::U8),
+        AUDIO_S8 => Ok(audio::AudioFormat::S8),
+        AUDIO_U16LSB => Ok(audio::AudioFormat::U16LSB),
+        AUDIO_U16MSB => Ok(audio::AudioFormat::U16MSB),
+        AUDIO_S16LSB => Ok(audio::AudioFormat::S16LSB),
+        AUDIO_S16MSB => Ok(audio::AudioFormat::S16MSB),
+        AUDIO_S32LSB => Ok(audio::AudioFormat::S32LSB),
+        AUDIO_S32MSB => Ok(audio::AudioFormat::S32MSB),
+        AUDIO_F32LSB => Ok(audio::AudioFormat::F32LSB),
+        AUDIO_F32MSB => Ok(audio::AudioFormat::F32MSB),
+        _ => Err(format!("Unsupported audio format 0x{:x}", format)),
+    }
+}
+
+/// Returns the size in bytes of one sample in the given format.
+pub fn sample_size(format: AudioFormat) -> Result<usize, String> {
+    match format {
+        AUDIO_U8 | AUDIO_S8 => Ok(1),
+        AUDIO_U16LSB | AUDIO_U16MSB | AUDIO_S16LSB | AUDIO_S16MSB => Ok(2),
+        AUDIO_S32LSB | AUDIO_S32MSB | AUDIO_F32LSB | AUDIO_F32MSB => Ok(4),
+        _ => Err(format!("Unsupported audio format 0x{:x}", format)),
+    }
+}
+
+/// Decodes one sample in the given format, `b` being exactly one sample long.
+fn decode_sample(format: AudioFormat, b: &[u8]) -> f32 {
+    match format {
+        AUDIO_U8 => (f32::from(b[0]) - 128.0) / 128.0,
+        AUDIO_S8 => f32::from(b[0] as i8) / 128.0,
+        AUDIO_U16LSB => (f32::from(u16::from_le_bytes([b[0], b[1]])) - 32_768.0) / 32_768.0,
+        AUDIO_U16MSB => (f32::from(u16::from_be_bytes([b[0], b[1]])) - 32_768.0) / 32_768.0,
+        AUDIO_S16LSB => f32::from(i16::from_le_bytes([b[0], b[1]])) / 32_768.0,
+        AUDIO_S16MSB => f32::from(i16::from_be_bytes([b[0], b[1]])) / 32_768.0,
+        AUDIO_S32LSB => i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f32 / 2_147_483_648.0,
+        AUDIO_S32MSB => i32::from_be_bytes([b[0], b[1], b[2], b[3]]) as f32 / 2_147_483_648.0,
+        AUDIO_F32LSB => f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+        _ => f32::from_be_bytes([b[0], b[1], b[2], b[3]]),
+    }
+}
+
+/// Encodes one sample in the given format into `out`, which is exactly one
+/// sample long, clipping it to the range of the format.
+fn encode_sample(format: AudioFormat, sample: f32, out: &mut [u8]) {
+    let sample = sample.clamp(-1.0, 1.0);
+    let unsigned_16 = (sample * 32_767.0 + 32_768.0).round() as u16;
+    let signed_16 = (sample * 32_767.0).round() as i16;
+    let signed_32 = (f64::from(sample) * 2_147_483_647.0).round() as i32;
+    match format {
+        AUDIO_U8 => out[0] = (sample * 127.0 + 128.0).round() as u8,
+        AUDIO_S8 => out[0] = (sample * 127.0).round() as i8 as u8,
+        AUDIO_U16LSB => out.copy_from_slice(&unsigned_16.to_le_bytes()),
+        AUDIO_U16MSB => out.copy_from_slice(&unsigned_16.to_be_bytes()),
+        AUDIO_S16LSB => out.copy_from_slice(&signed_16.to_le_bytes()),
+        AUDIO_S16MSB => out.copy_from_slice(&signed_16.to_be_bytes()),
+        AUDIO_S32LSB => out.copy_from_slice(&signed_32.to_le_bytes()),
+        AUDIO_S32MSB => out.copy_from_slice(&signed_32.to_be_bytes()),
+        AUDIO_F32LSB => out.copy_from_slice(&sample.to_le_bytes()),
+        _ => out.copy_from_slice(&sample.to_be_bytes()),
+    }
+}
+
+/// Decodes raw PCM data in the given format to float samples.
+pub fn decode(format: AudioFormat, bytes: &[u8]) -> Result<Vec<f32>, String> {
+    let size = sample_size(format)?;
+    Ok(bytes
+        .chunks_exact(size)
+        .map(|b| decode_sample(format, b))
+        .collect())
+}
+
+/// Encodes float samples to raw PCM data in the given format, clipping them
+/// to the range of the format.
+pub fn encode(format: AudioFormat, samples: &[f32]) -> Result<Vec<u8>, String> {
+    let size = sample_size(format)?;
+    let mut bytes = vec![0; samples.len() * size];
+    for (&sample, out) in samples.iter().zip(bytes.chunks_exact_mut(size)) {
+        encode_sample(format, sample, out);
+    }
+    Ok(bytes)
+}
+
+/// Multiplies raw PCM data in the given format by `gain` in place, without
+/// allocating, so that it can be used from the audio thread.
+pub fn scale(format: AudioFormat, bytes: &mut [u8], gain: f32) -> Result<(), String> {
+    let size = sample_size(format)?;
+    for b in bytes.chunks_exact_mut(size) {
+        let sample = decode_sample(format, b);
+        encode_sample(format, sample * gain, b);
+    }
+    Ok(())
+}
+
+/// Adds `other` scaled by `gain` to `samples`, the shorter of the two being
+/// padded with silence. The sum is clipped when encoded.
+pub fn mix(samples: &[f32], other: &[f32], gain: f32) -> Vec<f32> {
+    (0..samples.len().max(other.len()))
+        .map(|i| {
+            samples.get(i).cloned().unwrap_or(0.0) + other.get(i).cloned().unwrap_or(0.0) * gain
+        })
+        .collect()
+}
+
+/// Ramps the amplitude of the first `frames` frames of interleaved samples
+/// linearly up from silence, the first frame being silent.
+pub fn fade_in(samples: &mut [f32], channels: usize, frames: usize) {
+    for (i, frame) in samples.chunks_mut(channels).take(frames).enumerate() {
+        let gain = i as f32 / frames as f32;
+        for sample in frame {
+            *sample *= gain;
+        }
+    }
+}
+
+/// Reverses the order of the frames of raw PCM data, keeping the samples of
+/// each frame in order so that channels are not swapped.
+pub fn reverse_frames(bytes: &[u8], frame_size: usize) -> Vec<u8> {
+    let mut reversed = Vec::with_capacity(bytes.len());
+    for frame in bytes.chunks_exact(frame_size).rev() {
+        reversed.extend_from_slice(frame);
+    }
+    reversed
+}
+
+/// Returns the range of bytes of raw PCM data between the first and the last
+/// frame with a sample louder than `threshold`, in the `0.0..=1.0` range, or
+/// `None` if every frame is quieter.
+pub fn audible_range(
+    format: AudioFormat,
+    channels: usize,
+    bytes: &[u8],
+    threshold: f32,
+) -> Result<Option<Range<usize>>, String> {
+    let frame_size = sample_size(format)? * channels;
+    let is_audible = |frame: &[u8]| {
+        frame
+            .chunks_exact(frame.len() / channels)
+            .any(|sample| decode_sample(format, sample).abs() > threshold)
+    };
+    let mut frames = bytes.chunks_exact(frame_size);
+    let first = match frames.position(is_audible) {
+        Some(first) => first,
+        None => return Ok(None),
+    };
+    let last = bytes
+        .chunks_exact(frame_size)
+        .rposition(is_audible)
+        .unwrap_or(first);
+    Ok(Some(first * frame_size..(last + 1) * frame_size))
+}
+
+/// A one-pole low-pass filter over interleaved samples, keeping the state of
+/// each channel between calls so that it can process a stream in blocks.
+pub struct LowPass {
+    alpha: f32,
+    state: Vec<f32>,
+}
+
+impl LowPass {
+    /// Creates a filter cutting frequencies above `cutoff_hz` for a stream
+    /// at the given sample rate.
+    pub fn new(cutoff_hz: f32, frequency: i32, channels: usize) -> LowPass {
+        let cutoff = cutoff_hz.max(0.0) / frequency.max(1) as f32;
+        LowPass {
+            alpha: 1.0 - (-2.0 * std::f32::consts::PI * cutoff).exp(),
+            state: vec![0.0; channels.max(1)],
+        }
+    }
+
+    /// Filters raw PCM data in the given format in place, without
+    /// allocating, so that it can be used from the audio thread.
+    pub fn process(&mut self, format: AudioFormat, bytes: &mut [u8]) -> Result<(), String> {
+        let size = sample_size(format)?;
+        let channels = self.state.len();
+        for (i, b) in bytes.chunks_exact_mut(size).enumerate() {
+            let previous = &mut self.state[i % channels];
+            *previous += self.alpha * (decode_sample(format, b) - *previous);
+            encode_sample(format, *previous, b);
+        }
+        Ok(())
+    }
+}
+
+/// Resamples interleaved samples with linear interpolation, reading the input
+/// `ratio` times faster than it was recorded.
+pub fn resample(samples: &[f32], channels: usize, ratio: f64) -> Vec<f32> {
+    let frames = samples.len() / channels;
+    if frames == 0 {
+        return Vec::new();
+    }
+    let out_frames = (frames as f64 / ratio).ceil() as usize;
+    let mut out = Vec::with_capacity(out_frames * channels);
+    for i in 0..out_frames {
+        let position = i as f64 * ratio;
+        let index = (position as usize).min(frames - 1);
+        let next = (index + 1).min(frames - 1);
+        let frac = (position - index as f64).min(1.0) as f32;
+        for c in 0..channels {
+            let a = samples[index * channels + c];
+            let b = samples[next * channels + c];
+            out.push(a + (b - a) * frac);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let formats = [
+            AUDIO_U8,
+            AUDIO_S8,
+            AUDIO_U16LSB,
+            AUDIO_U16MSB,
+            AUDIO_S16LSB,
+            AUDIO_S16MSB,
+            AUDIO_S32LSB,
+            AUDIO_S32MSB,
+            AUDIO_F32LSB,
+            AUDIO_F32MSB,
+        ];
+        let samples = [0.0, 0.5, -0.5, 1.0, -1.0];
+        for &format in formats.iter() {
+            let decoded = decode(format, &encode(format, &samples).unwrap()).unwrap();
+            for (a, b) in samples.iter().zip(decoded.iter()) {
+                assert!(
+                    (a - b).abs() < 0.01,
+                    "format 0x{:x}: {} != {}",
+                    format,
+                    a,
+                    b
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn scale_in_place() {
+        let mut bytes = encode(AUDIO_S16LSB, &[0.5, -0.5]).unwrap();
+        scale(AUDIO_S16LSB, &mut bytes, 0.5).unwrap();
+        assert_eq!(bytes, encode(AUDIO_S16LSB, &[0.25, -0.25]).unwrap());
+    }
+
+    #[test]
+    fn lowpass_attenuates_high_frequencies() {
+        let mut steady = encode(AUDIO_F32LSB, &[0.5; 256]).unwrap();
+        LowPass::new(1_000.0, 44_100, 1)
+            .process(AUDIO_F32LSB, &mut steady)
+            .unwrap();
+        let steady = decode(AUDIO_F32LSB, &steady).unwrap();
+        assert!((steady[255] - 0.5).abs() < 0.01);
+
+        let alternating: Vec<f32> = (0..256)
+            .map(|i| if i % 2 == 0 { 0.5 } else { -0.5 })
+            .collect();
+        let mut alternating = encode(AUDIO_F32LSB, &alternating).unwrap();
+        LowPass::new(1_000.0, 44_100, 1)
+            .process(AUDIO_F32LSB, &mut alternating)
+            .unwrap();
+        let alternating = decode(AUDIO_F32LSB, &alternating).unwrap();
+        assert!(alternating[128..].iter().all(|sample| sample.abs() < 0.1));
+    }
+
+    #[test]
+    fn mix_pads_and_clips() {
+        let mixed = mix(&[0.5, 0.5, 0.5], &[1.0, -1.0], 0.5);
+        assert_eq!(mixed, vec![1.0, 0.0, 0.5]);
+        let clipped = encode(AUDIO_S16LSB, &mix(&[0.75], &[0.75], 1.0)).unwrap();
+        assert_eq!(clipped, encode(AUDIO_S16LSB, &[1.0]).unwrap());
+    }
+
+    #[test]
+    fn fade_in_ramps_frames() {
+        let mut samples = [1.0, -1.0, 1.0, -1.0, 1.0, -1.0, 1.0, -1.0, 1.0, -1.0];
+        fade_in(&mut samples, 2, 4);
+        assert_eq!(
+            samples,
+            [0.0, 0.0, 0.25, -0.25, 0.5, -0.5, 0.75, -0.75, 1.0, -1.0]
+        );
+    }
+
+    #[test]
+    fn reverse_keeps_channels() {
+        let bytes = [1, 2, 3, 4, 5, 6, 7, 8];
+        assert_eq!(reverse_frames(&bytes, 4), vec![5, 6, 7, 8, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn audible_range_skips_silence() {
+        let bytes = encode(AUDIO_S16LSB, &[0.0, 0.001, 0.5, 0.0, 0.0, -0.5, 0.0, 0.0]).unwrap();
+        assert_eq!(
+            audible_range(AUDIO_S16LSB, 2, &bytes, 0.01).unwrap(),
+            Some(4..12)
+        );
+        assert_eq!(audible_range(AUDIO_S16LSB, 1, &bytes, 0.9).unwrap(), None);
+    }
+
+    #[test]
+    fn resample_halves_length() {
+        let samples = [0.0, 0.0, 1.0, 1.0, 2.0, 2.0, 3.0, 3.0];
+        assert_eq!(resample(&samples, 2, 2.0), vec![0.0, 0.0, 2.0, 2.0]);
+    }
+}
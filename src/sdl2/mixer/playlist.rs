@@ -0,0 +1,191 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::Music;
+
+// Set by the music finished hook. The hook runs on the audio thread where
+// SDL2_mixer functions must not be called, so the playlist only advances in
+// `Playlist::update`.
+static TRACK_FINISHED: AtomicBool = AtomicBool::new(false);
+
+fn track_finished() {
+    TRACK_FINISHED.store(true, Ordering::SeqCst);
+}
+
+/// What a `Playlist` does when a track finishes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RepeatMode {
+    /// Play the tracks once, in order, and stop after the last one.
+    None,
+    /// Play the current track over and over.
+    One,
+    /// Play the tracks in order, starting over after the last one.
+    All,
+}
+
+/// A list of music tracks played one after the other.
+///
+/// The playlist installs its own music finished hook when it starts playing,
+/// replacing any hook set with `Music::hook_finished`. Call `update`
+/// regularly, for instance once per frame, to start the next track when the
+/// current one is over.
+pub struct Playlist<'a> {
+    tracks: Vec<Music<'a>>,
+    current: usize,
+    repeat: RepeatMode,
+    playing: bool,
+}
+
+impl<'a> Playlist<'a> {
+    /// Creates a playlist of the given tracks, starting at the first one.
+    pub fn new(tracks: Vec<Music<'a>>) -> Playlist<'a> {
+        Playlist {
+            tracks,
+            current: 0,
+            repeat: RepeatMode::None,
+            playing: false,
+        }
+    }
+
+    /// The tracks of the playlist, in playing order.
+    pub fn tracks(&self) -> &[Music<'a>] {
+        &self.tracks
+    }
+
+    pub fn len(&self) -> usize {
+        self.tracks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tracks.is_empty()
+    }
+
+    /// The index of the current track, or `None` if the playlist is empty.
+    pub fn current_index(&self) -> Option<usize> {
+        if self.tracks.is_empty() {
+            None
+        } else {
+            Some(self.current)
+        }
+    }
+
+    /// The current track, or `None` if the playlist is empty.
+    pub fn current(&self) -> Option<&Music<'a>> {
+        self.tracks.get(self.current)
+    }
+
+    pub fn repeat(&self) -> RepeatMode {
+        self.repeat
+    }
+
+    pub fn set_repeat(&mut self, mode: RepeatMode) {
+        self.repeat = mode;
+    }
+
+    /// If the playlist has been started and has not stopped yet.
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// Starts playing the current track.
+    pub fn play(&mut self) -> Result<(), String> {
+        if self.tracks.is_empty() {
+            return Err("The playlist is empty".to_owned());
+        }
+        Music::hook_finished(track_finished);
+        self.play_current()
+    }
+
+    /// Halts the music and stops the playlist, keeping the current track.
+    pub fn stop(&mut self) {
+        self.playing = false;
+        Music::unhook_finished();
+        Music::halt();
+        TRACK_FINISHED.store(false, Ordering::SeqCst);
+    }
+
+    /// Moves to the next track, wrapping around after the last one, and
+    /// plays it if the playlist is playing.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Result<(), String> {
+        if self.tracks.is_empty() {
+            return Ok(());
+        }
+        self.current = (self.current + 1) % self.tracks.len();
+        self.restart()
+    }
+
+    /// Moves to the previous track, wrapping around before the first one,
+    /// and plays it if the playlist is playing.
+    pub fn prev(&mut self) -> Result<(), String> {
+        if self.tracks.is_empty() {
+            return Ok(());
+        }
+        self.current = (self.current + self.tracks.len() - 1) % self.tracks.len();
+        self.restart()
+    }
+
+    /// Shuffles the tracks. The current track is moved to the front so that
+    /// the music playing is not interrupted.
+    pub fn shuffle(&mut self) {
+        if self.tracks.is_empty() {
+            return;
+        }
+        self.tracks.swap(0, self.current);
+        self.current = 0;
+
+        // xorshift64*, seeded from the clock: good enough to shuffle songs
+        let mut state = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0)
+            | 1;
+        let mut random = move || {
+            state ^= state >> 12;
+            state ^= state << 25;
+            state ^= state >> 27;
+            state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+        };
+        for i in (2..self.tracks.len()).rev() {
+            let j = 1 + (random() % i as u64) as usize;
+            self.tracks.swap(i, j);
+        }
+    }
+
+    /// Starts the next track according to the repeat mode if the current one
+    /// has finished. Returns `true` if a new track was started.
+    pub fn update(&mut self) -> Result<bool, String> {
+        if !TRACK_FINISHED.swap(false, Ordering::SeqCst) || !self.playing {
+            return Ok(false);
+        }
+        match self.repeat {
+            RepeatMode::One => {}
+            RepeatMode::All => self.current = (self.current + 1) % self.tracks.len(),
+            RepeatMode::None => {
+                if self.current + 1 == self.tracks.len() {
+                    self.stop();
+                    return Ok(false);
+                }
+                self.current += 1;
+            }
+        }
+        self.play_current()?;
+        Ok(true)
+    }
+
+    fn restart(&mut self) -> Result<(), String> {
+        if self.playing {
+            self.play_current()
+        } else {
+            Ok(())
+        }
+    }
+
+    fn play_current(&mut self) -> Result<(), String> {
+        // playing over the current music doesn't call the finished hook
+        TRACK_FINISHED.store(false, Ordering::SeqCst);
+        let result = self.tracks[self.current].play(1);
+        self.playing = result.is_ok();
+        result
+    }
+}
@@ -0,0 +1,53 @@
+//! The playback speed set on each channel with `Channel::set_speed`.
+//!
+//! A channel playing at another speed plays a resampled copy of the chunk,
+//! which is kept here until the channel plays something else, as
+//! `SDL2_mixer` reads it for as long as it plays.
+
+use libc::c_int;
+use std::collections::HashMap;
+use std::mem;
+use std::sync::{Mutex, MutexGuard};
+
+use super::Chunk;
+
+#[derive(Default)]
+struct ChannelSpeed {
+    factor: Option<f32>,
+    playing: Option<Chunk>,
+}
+
+// the chunks are only reached through the lock
+unsafe impl Send for ChannelSpeed {}
+
+lazy_static! {
+    static ref SPEEDS: Mutex<HashMap<c_int, ChannelSpeed>> = Mutex::new(HashMap::new());
+}
+
+fn speeds() -> MutexGuard<'static, HashMap<c_int, ChannelSpeed>> {
+    SPEEDS.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Sets the speed of the channel, `None` playing chunks as they are.
+pub fn set_speed(channel: c_int, factor: Option<f32>) {
+    speeds().entry(channel).or_default().factor = factor;
+}
+
+pub fn speed(channel: c_int) -> Option<f32> {
+    speeds().get(&channel).and_then(|speed| speed.factor)
+}
+
+/// Keeps the chunk the channel started playing alive, in place of the
+/// previous one.
+///
+/// The previous chunk is dropped after the lock is released: freeing a chunk
+/// halts the channels still playing it, which calls the channel finished
+/// callback, which may start playing again.
+pub fn set_playing(channel: c_int, chunk: Option<Chunk>) {
+    let previous = {
+        let mut speeds = speeds();
+        let speed = speeds.entry(channel).or_default();
+        mem::replace(&mut speed.playing, chunk)
+    };
+    drop(previous);
+}
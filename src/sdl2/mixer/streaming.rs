@@ -0,0 +1,98 @@
+use libc::{c_int, c_void};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::ptr;
+use std::slice;
+use sys::mixer;
+
+type Source = Box<dyn Read + Send>;
+
+unsafe extern "C" fn c_streaming_music_hook(udata: *mut c_void, stream: *mut u8, len: c_int) {
+    let source = &mut *(udata as *mut Source);
+    let buf = slice::from_raw_parts_mut(stream, len as usize);
+    let mut filled = 0;
+    while filled < buf.len() {
+        match source.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(_) => break,
+        }
+    }
+    // SDL2_mixer fills the stream with silence before calling the hook, so
+    // whatever could not be read stays silent
+}
+
+/// Starts over from the beginning of the source when reaching its end.
+struct Looping<R> {
+    inner: R,
+}
+
+impl<R: Read + Seek> Read for Looping<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.inner.read(buf)? {
+            0 if !buf.is_empty() => {
+                self.inner.seek(SeekFrom::Start(0))?;
+                self.inner.read(buf)
+            }
+            n => Ok(n),
+        }
+    }
+}
+
+/// A sound streamed from a reader as it plays, instead of being loaded in
+/// memory whole like a `Chunk`. Meant for long sounds like ambient loops.
+///
+/// The reader must provide raw audio data in the format returned by
+/// `query_spec`, and is read from the audio thread, so it should not block
+/// for long.
+///
+/// The sound is played in place of the music, through `Mix_HookMusic`:
+/// `SDL2_mixer` only has one music stream, so music can't play while a
+/// `StreamingChunk` does, and only one `StreamingChunk` plays at a time.
+pub struct StreamingChunk {
+    source: *mut Source,
+}
+
+impl StreamingChunk {
+    /// Creates a sound streamed from the given reader, which stops at the
+    /// end of the data.
+    pub fn new<R: Read + Send + 'static>(reader: R) -> StreamingChunk {
+        StreamingChunk {
+            source: Box::into_raw(Box::new(Box::new(reader) as Source)),
+        }
+    }
+
+    /// Creates a sound streamed from the given reader, which starts over
+    /// from the beginning when reaching the end of the data.
+    pub fn looping<R: Read + Seek + Send + 'static>(reader: R) -> StreamingChunk {
+        StreamingChunk::new(Looping { inner: reader })
+    }
+
+    /// Starts playing the sound, replacing the music or any other
+    /// `StreamingChunk` playing.
+    pub fn play(&self) {
+        unsafe { mixer::Mix_HookMusic(Some(c_streaming_music_hook), self.source as *mut c_void) }
+    }
+
+    /// Stops playing the sound. It resumes where it stopped if played again.
+    pub fn stop(&self) {
+        if self.is_playing() {
+            unsafe { mixer::Mix_HookMusic(None, ptr::null_mut()) }
+        }
+    }
+
+    /// If the sound is playing, that is if it was played and neither stopped
+    /// nor replaced since.
+    pub fn is_playing(&self) -> bool {
+        unsafe { mixer::Mix_GetMusicHookData() == self.source as *mut c_void }
+    }
+}
+
+impl Drop for StreamingChunk {
+    fn drop(&mut self) {
+        // Mix_HookMusic locks the audio device, so the hook is not running
+        // anymore once it returns
+        self.stop();
+        unsafe { drop(Box::from_raw(self.source)) };
+    }
+}
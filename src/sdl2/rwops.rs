@@ -1,3 +1,4 @@
+use crate::common::path_to_cstring;
 use crate::get_error;
 use libc::c_void;
 use libc::{c_char, c_int, size_t};
@@ -34,8 +35,8 @@ impl<'a> RWops<'a> {
     #[doc(alias = "SDL_RWFromFile")]
     pub fn from_file<P: AsRef<Path>>(path: P, mode: &str) -> Result<RWops<'static>, String> {
         let raw = unsafe {
-            let path_c = CString::new(path.as_ref().to_str().unwrap()).unwrap();
-            let mode_c = CString::new(mode).unwrap();
+            let path_c = path_to_cstring(path.as_ref())?;
+            let mode_c = CString::new(mode).map_err(|err| err.to_string())?;
             sys::SDL_RWFromFile(
                 path_c.as_ptr() as *const c_char,
                 mode_c.as_ptr() as *const c_char,
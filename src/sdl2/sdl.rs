@@ -355,10 +355,21 @@ pub fn init() -> Result<Sdl, String> {
     Sdl::new()
 }
 
+/// Returns the message of the last error reported by SDL or one of its
+/// libraries.
+///
+/// SDL keeps one message per thread, so errors reported on other threads
+/// don't show up here. The message is only meaningful right after the call
+/// that failed, as any later SDL call on the same thread may replace it:
+/// the functions of this crate read it before making any other SDL call.
+/// Invalid UTF-8 in the message is replaced rather than panicking.
+#[doc(alias = "SDL_GetError")]
 pub fn get_error() -> String {
     unsafe {
         let err = sys::SDL_GetError();
-        CStr::from_ptr(err as *const _).to_str().unwrap().to_owned()
+        CStr::from_ptr(err as *const _)
+            .to_string_lossy()
+            .into_owned()
     }
 }
 
@@ -100,6 +100,12 @@ impl<'a> AsMut<SurfaceRef> for Surface<'a> {
 }
 
 impl<'a> Surface<'a> {
+    /// Wraps a surface created by SDL or one of its libraries, taking
+    /// ownership of it: it is freed with `SDL_FreeSurface` once the returned
+    /// `Surface` and any clone of its context are dropped.
+    ///
+    /// This is how bindings like `sdl2::ttf` and `sdl2::image` turn the
+    /// surfaces they get into a `Surface`, without depending on its layout.
     pub unsafe fn from_ll<'b>(raw: *mut sys::SDL_Surface) -> Surface<'b> {
         let context = SurfaceContext {
             raw,
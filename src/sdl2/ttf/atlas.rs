@@ -0,0 +1,136 @@
+use pixels::{Color, PixelFormatEnum};
+use rect::Rect;
+use render::BlendMode;
+use surface::{Surface, SurfaceRef};
+
+use super::font::{Font, FontError, FontResult};
+
+const FIRST: u8 = b' ';
+const LAST: u8 = b'~';
+// drawn in place of the characters the atlas doesn't hold
+const REPLACEMENT: u8 = b'?';
+
+/// Where a glyph is in the atlas, and where to blit it from the pen.
+struct AtlasGlyph {
+    rect: Rect,
+    x_offset: i32,
+    y_offset: i32,
+    advance: i32,
+}
+
+/// The printable ASCII characters of a font rendered once into a single
+/// surface, to draw frequently changing text like FPS counters by blitting
+/// glyphs instead of rendering every string.
+///
+/// Glyphs are rendered in *blended* mode and in white, then tinted with the
+/// color modulation of the atlas when drawn. Kerning is not applied, and the
+/// atlas doesn't follow later changes to the style or outline of the font.
+pub struct AsciiAtlas {
+    surface: Surface<'static>,
+    glyphs: Vec<AtlasGlyph>,
+    height: i32,
+}
+
+impl AsciiAtlas {
+    /// Renders the characters from `' '` to `'~'` of the given font.
+    pub fn new(font: &Font) -> FontResult<AsciiAtlas> {
+        let blits = (FIRST..=LAST)
+            .map(|byte| font.glyph_blit_info(byte as char, Color::WHITE))
+            .collect::<FontResult<Vec<_>>>()?;
+
+        let width = blits.iter().map(|blit| blit.surface.width()).sum::<u32>();
+        let height = blits
+            .iter()
+            .map(|blit| blit.surface.height())
+            .max()
+            .unwrap_or(1);
+        let mut surface =
+            Surface::new(width, height, PixelFormatEnum::ARGB8888).map_err(FontError::SdlError)?;
+        surface
+            .set_blend_mode(BlendMode::Blend)
+            .map_err(FontError::SdlError)?;
+
+        let mut glyphs = Vec::with_capacity(blits.len());
+        let mut x = 0;
+        for mut blit in blits {
+            let rect = Rect::new(x, 0, blit.surface.width(), blit.surface.height());
+            // the glyphs are copied as they are, alpha included
+            blit.surface
+                .set_blend_mode(BlendMode::None)
+                .map_err(FontError::SdlError)?;
+            blit.surface
+                .blit(None, &mut surface, rect)
+                .map_err(FontError::SdlError)?;
+            glyphs.push(AtlasGlyph {
+                rect,
+                x_offset: blit.x_offset,
+                y_offset: blit.y_offset,
+                advance: blit.advance,
+            });
+            x += rect.width() as i32;
+        }
+
+        Ok(AsciiAtlas {
+            surface,
+            glyphs,
+            height: font.height(),
+        })
+    }
+
+    /// The surface holding the glyphs.
+    pub fn surface(&self) -> &SurfaceRef {
+        &self.surface
+    }
+
+    /// The height of a line of text, as given by the font.
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    fn glyph(&self, ch: char) -> &AtlasGlyph {
+        let byte = if (FIRST as char..=LAST as char).contains(&ch) {
+            ch as u8
+        } else {
+            REPLACEMENT
+        };
+        &self.glyphs[(byte - FIRST) as usize]
+    }
+
+    /// Returns the width of the given text when drawn with `draw_str`.
+    pub fn width_of(&self, text: &str) -> i32 {
+        text.chars().map(|ch| self.glyph(ch).advance).sum()
+    }
+
+    /// Draws the given text on the target surface, with the top left of the
+    /// line at `(x, y)`, tinted with the given color and its alpha.
+    ///
+    /// Characters outside of the printable ASCII range are drawn as `'?'`.
+    /// Returns the horizontal position following the text.
+    pub fn draw_str(
+        &mut self,
+        target: &mut SurfaceRef,
+        x: i32,
+        y: i32,
+        text: &str,
+        color: Color,
+    ) -> FontResult<i32> {
+        self.surface.set_color_mod(color);
+        self.surface.set_alpha_mod(color.a);
+
+        let mut pen = x;
+        for ch in text.chars() {
+            let glyph = self.glyph(ch);
+            let dst = Rect::new(
+                pen + glyph.x_offset,
+                y + glyph.y_offset,
+                glyph.rect.width(),
+                glyph.rect.height(),
+            );
+            self.surface
+                .blit(glyph.rect, target, dst)
+                .map_err(FontError::SdlError)?;
+            pen += glyph.advance;
+        }
+        Ok(pen)
+    }
+}
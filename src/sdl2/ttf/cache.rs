@@ -0,0 +1,282 @@
+use pixels::Color;
+use std::collections::HashMap;
+use std::rc::Rc;
+use surface::Surface;
+
+#[cfg(feature = "unicode-normalization")]
+use super::font::NormalizationForm;
+use super::font::{AlphaMode, Font, FontResult, FontSource, FontStyle, Hinting, RenderMode};
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    text: String,
+    style: FontStyle,
+    color: Color,
+    mode: RenderMode,
+}
+
+struct CacheEntry {
+    surface: Surface<'static>,
+    last_used: u64,
+}
+
+/// The font a cache was filled with, and its settings that change the
+/// rendering besides the style, which is part of the key.
+///
+/// Fonts are identified by their source rather than by their address, see
+/// `FaceKey`.
+#[derive(PartialEq)]
+struct FontState {
+    source: FontSource,
+    outline_width: u16,
+    hinting: Hinting,
+    kerning: bool,
+    substitute: Option<char>,
+    #[cfg(feature = "unicode-normalization")]
+    normalization: NormalizationForm,
+    output_alpha: AlphaMode,
+}
+
+impl FontState {
+    fn of(font: &Font) -> FontState {
+        FontState {
+            source: font.source(),
+            outline_width: font.get_outline_width(),
+            hinting: font.get_hinting(),
+            kerning: font.get_kerning(),
+            substitute: font.substitute(),
+            #[cfg(feature = "unicode-normalization")]
+            normalization: font.normalization(),
+            output_alpha: font.output_alpha(),
+        }
+    }
+}
+
+/// A cache of whole rendered strings, for text that is drawn again and again
+/// like menus and HUD labels.
+///
+/// Surfaces are keyed on the text, the style of the font, the color and the
+/// rendering mode. When the cache is full, the least recently used surface
+/// is dropped. A cache is meant to be used with a single font: it is cleared
+/// when it is used with a font opened from another file, face or size, or
+/// after the outline, hinting, kerning, substitute character, normalization
+/// or output alpha of the font changed.
+pub struct TextCache {
+    capacity: usize,
+    entries: HashMap<CacheKey, CacheEntry>,
+    font: Option<FontState>,
+    clock: u64,
+}
+
+impl TextCache {
+    /// Creates a cache holding up to `capacity` rendered strings.
+    pub fn new(capacity: usize) -> TextCache {
+        TextCache {
+            capacity: capacity.max(1),
+            entries: HashMap::new(),
+            font: None,
+            clock: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The number of rendered strings in the cache.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Drops all the rendered strings.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Returns the given text rendered with the font, rendering it only if
+    /// it is not in the cache yet.
+    pub fn render(
+        &mut self,
+        font: &Font,
+        text: &str,
+        color: Color,
+        mode: RenderMode,
+    ) -> FontResult<&Surface<'static>> {
+        let state = FontState::of(font);
+        if self.font.as_ref() != Some(&state) {
+            self.entries.clear();
+            self.font = Some(state);
+        }
+
+        let key = CacheKey {
+            text: text.to_owned(),
+            style: font.get_style(),
+            color,
+            mode,
+        };
+        if !self.entries.contains_key(&key) {
+            let surface = font.render(text).with_mode(color, mode)?;
+            if self.entries.len() >= self.capacity {
+                self.evict_least_recently_used();
+            }
+            self.entries.insert(
+                key.clone(),
+                CacheEntry {
+                    surface,
+                    last_used: 0,
+                },
+            );
+        }
+
+        self.clock += 1;
+        let entry = self.entries.get_mut(&key).unwrap();
+        entry.last_used = self.clock;
+        Ok(&entry.surface)
+    }
+
+    fn evict_least_recently_used(&mut self) {
+        let oldest = self
+            .entries
+            .iter()
+            .min_by_key(|&(_, entry)| entry.last_used)
+            .map(|(key, _)| key.clone());
+        if let Some(key) = oldest {
+            self.entries.remove(&key);
+        }
+    }
+}
+
+/// The face a shared cache entry was rendered with, and the settings of the
+/// font that change the rendering besides its style.
+///
+/// Fonts are identified by their source rather than by their address, which
+/// may be reused by another font once they are dropped.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct FaceKey {
+    source: FontSource,
+    outline_width: u16,
+    hinting: i32,
+    kerning: bool,
+    substitute: Option<char>,
+    #[cfg(feature = "unicode-normalization")]
+    normalization: NormalizationForm,
+    output_alpha: AlphaMode,
+}
+
+impl FaceKey {
+    fn of(font: &Font) -> FaceKey {
+        FaceKey {
+            source: font.source(),
+            outline_width: font.get_outline_width(),
+            hinting: font.get_hinting() as i32,
+            kerning: font.get_kerning(),
+            substitute: font.substitute(),
+            #[cfg(feature = "unicode-normalization")]
+            normalization: font.normalization(),
+            output_alpha: font.output_alpha(),
+        }
+    }
+}
+
+struct SharedEntry {
+    surface: Rc<Surface<'static>>,
+    last_used: u64,
+}
+
+/// A cache of rendered strings shared between the places drawing them, so
+/// that a label drawn in ten places is rendered once.
+///
+/// Unlike `TextCache`, this cache can be used with any number of fonts:
+/// surfaces are keyed on the file, face, size and settings of the font as
+/// well as on the text, the color and the rendering mode, and handed out as
+/// `Rc`s. Fonts opened from the same file, face and size share their
+/// surfaces, while fonts loaded from an `RWops` only share with themselves.
+/// When the cache is full, the least recently used surface is dropped from
+/// it, and freed once no `Rc` holds it anymore.
+pub struct SharedTextCache {
+    capacity: usize,
+    entries: HashMap<(FaceKey, CacheKey), SharedEntry>,
+    clock: u64,
+}
+
+impl SharedTextCache {
+    /// Creates a cache holding up to `capacity` rendered strings.
+    pub fn new(capacity: usize) -> SharedTextCache {
+        SharedTextCache {
+            capacity: capacity.max(1),
+            entries: HashMap::new(),
+            clock: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The number of rendered strings in the cache.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Drops all the rendered strings from the cache.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Returns the given text rendered with the font, rendering it only if
+    /// it is not in the cache yet.
+    pub fn render(
+        &mut self,
+        font: &Font,
+        text: &str,
+        color: Color,
+        mode: RenderMode,
+    ) -> FontResult<Rc<Surface<'static>>> {
+        let key = (
+            FaceKey::of(font),
+            CacheKey {
+                text: text.to_owned(),
+                style: font.get_style(),
+                color,
+                mode,
+            },
+        );
+        if !self.entries.contains_key(&key) {
+            let surface = Rc::new(font.render(text).with_mode(color, mode)?);
+            if self.entries.len() >= self.capacity {
+                self.evict_least_recently_used();
+            }
+            self.entries.insert(
+                key.clone(),
+                SharedEntry {
+                    surface,
+                    last_used: 0,
+                },
+            );
+        }
+
+        self.clock += 1;
+        let entry = self.entries.get_mut(&key).unwrap();
+        entry.last_used = self.clock;
+        Ok(entry.surface.clone())
+    }
+
+    fn evict_least_recently_used(&mut self) {
+        let oldest = self
+            .entries
+            .iter()
+            .min_by_key(|&(_, entry)| entry.last_used)
+            .map(|(key, _)| key.clone());
+        if let Some(key) = oldest {
+            self.entries.remove(&key);
+        }
+    }
+}
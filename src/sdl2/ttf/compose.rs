@@ -0,0 +1,299 @@
+//! Helpers used to post-process and combine rendered surfaces.
+
+use get_error;
+use pixels::{Color, Palette, PixelFormatEnum};
+use rect::Rect;
+use surface::{Surface, SurfaceRef};
+use sys;
+
+use super::font::{FontError, FontResult};
+
+/// Copies the given area of a surface into a new surface of the same pixel
+/// format. The palette of indexed surfaces is carried over.
+pub fn crop_surface<'a>(surface: &SurfaceRef, rect: Rect) -> FontResult<Surface<'a>> {
+    let format = surface.pixel_format_enum();
+    let mut cropped =
+        Surface::new(rect.width(), rect.height(), format).map_err(FontError::SdlError)?;
+    unsafe {
+        let palette = (*(*surface.raw()).format).palette;
+        if !palette.is_null() && sys::SDL_SetSurfacePalette(cropped.raw(), palette) != 0 {
+            return Err(FontError::SdlError(get_error()));
+        }
+    }
+
+    let bpp = format.byte_size_per_pixel();
+    let src_pitch = surface.pitch() as usize;
+    let dst_pitch = cropped.pitch() as usize;
+    let row_len = rect.width() as usize * bpp;
+    surface.with_lock(|src| {
+        cropped.with_lock_mut(|dst| {
+            for y in 0..rect.height() as usize {
+                let src_start = (rect.y() as usize + y) * src_pitch + rect.x() as usize * bpp;
+                let dst_start = y * dst_pitch;
+                dst[dst_start..dst_start + row_len]
+                    .copy_from_slice(&src[src_start..src_start + row_len]);
+            }
+        })
+    });
+    Ok(cropped)
+}
+
+/// Rotates a 32-bit `ARGB8888` surface clockwise by the given number of
+/// quarter turns, moving pixels without resampling them.
+pub fn rotate_quarter_turns<'a>(
+    surface: &SurfaceRef,
+    quarter_turns: u8,
+) -> FontResult<Surface<'a>> {
+    debug_assert_eq!(surface.pixel_format_enum(), PixelFormatEnum::ARGB8888);
+    let turns = quarter_turns % 4;
+    let (width, height) = surface.size();
+    let (rotated_width, rotated_height) = if turns.is_multiple_of(2) {
+        (width, height)
+    } else {
+        (height, width)
+    };
+    let mut rotated = Surface::new(rotated_width, rotated_height, PixelFormatEnum::ARGB8888)
+        .map_err(FontError::SdlError)?;
+    let (width, height) = (width as usize, height as usize);
+    let src_pitch = surface.pitch() as usize;
+    let dst_pitch = rotated.pitch() as usize;
+    surface.with_lock(|src| {
+        rotated.with_lock_mut(|dst| {
+            for y in 0..height {
+                for x in 0..width {
+                    let (dst_x, dst_y) = match turns {
+                        0 => (x, y),
+                        1 => (height - 1 - y, x),
+                        2 => (width - 1 - x, height - 1 - y),
+                        _ => (y, width - 1 - x),
+                    };
+                    let src_offset = y * src_pitch + x * 4;
+                    let dst_offset = dst_y * dst_pitch + dst_x * 4;
+                    dst[dst_offset..dst_offset + 4]
+                        .copy_from_slice(&src[src_offset..src_offset + 4]);
+                }
+            }
+        })
+    });
+    Ok(rotated)
+}
+
+/// Replaces every pixel of a 32-bit `ARGB8888` surface, like the ones
+/// rendered in *blended* mode, with the result of the given function. The
+/// function receives the coordinates and the value of the pixel.
+pub fn map_argb_pixels<F>(surface: &mut SurfaceRef, mut f: F)
+where
+    F: FnMut(u32, u32, u32) -> u32,
+{
+    debug_assert_eq!(surface.pixel_format_enum(), PixelFormatEnum::ARGB8888);
+    let (width, height) = surface.size();
+    let pitch = surface.pitch() as usize;
+    surface.with_lock_mut(|pixels| {
+        for y in 0..height {
+            let start = y as usize * pitch;
+            let row = &mut pixels[start..start + width as usize * 4];
+            for (x, bytes) in row.chunks_mut(4).enumerate() {
+                let pixel = u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+                bytes.copy_from_slice(&f(x as u32, y, pixel).to_ne_bytes());
+            }
+        }
+    });
+}
+
+/// Multiplies the color channels of an `ARGB8888` pixel by its alpha.
+pub fn premultiply_pixel(pixel: u32) -> u32 {
+    let alpha = pixel >> 24;
+    let scale = |shift: u32| ((((pixel >> shift) & 0xff) * alpha + 127) / 255) << shift;
+    (alpha << 24) | scale(16) | scale(8) | scale(0)
+}
+
+/// Composites an `ARGB8888` pixel over another one.
+fn over(src: u32, dst: u32) -> u32 {
+    let src_alpha = src >> 24;
+    let dst_alpha = dst >> 24;
+    if src_alpha == 255 || dst_alpha == 0 {
+        return src;
+    } else if src_alpha == 0 {
+        return dst;
+    }
+    // the part of the destination seen through the source
+    let dst_weight = dst_alpha * (255 - src_alpha) / 255;
+    let alpha = src_alpha + dst_weight;
+    let channel = |shift: u32| {
+        let src_channel = (src >> shift) & 0xff;
+        let dst_channel = (dst >> shift) & 0xff;
+        ((src_channel * src_alpha + dst_channel * dst_weight) / alpha) << shift
+    };
+    (alpha << 24) | channel(16) | channel(8) | channel(0)
+}
+
+/// Composites an `ARGB8888` surface over another one with its top-left
+/// corner at the given position, clipping what falls outside.
+pub fn draw_over(dst: &mut SurfaceRef, src: &SurfaceRef, x: i32, y: i32) {
+    debug_assert_eq!(src.pixel_format_enum(), PixelFormatEnum::ARGB8888);
+    debug_assert_eq!(dst.pixel_format_enum(), PixelFormatEnum::ARGB8888);
+    let (src_width, src_height) = src.size();
+    let (dst_width, dst_height) = dst.size();
+    let src_pitch = src.pitch() as usize;
+    let dst_pitch = dst.pitch() as usize;
+    let read = |pixels: &[u8], offset: usize| {
+        u32::from_ne_bytes([
+            pixels[offset],
+            pixels[offset + 1],
+            pixels[offset + 2],
+            pixels[offset + 3],
+        ])
+    };
+    src.with_lock(|src_pixels| {
+        dst.with_lock_mut(|dst_pixels| {
+            for src_y in 0..src_height as i32 {
+                let dst_y = y + src_y;
+                if dst_y < 0 || dst_y >= dst_height as i32 {
+                    continue;
+                }
+                for src_x in 0..src_width as i32 {
+                    let dst_x = x + src_x;
+                    if dst_x < 0 || dst_x >= dst_width as i32 {
+                        continue;
+                    }
+                    let src_offset = src_y as usize * src_pitch + src_x as usize * 4;
+                    let dst_offset = dst_y as usize * dst_pitch + dst_x as usize * 4;
+                    let pixel = over(read(src_pixels, src_offset), read(dst_pixels, dst_offset));
+                    dst_pixels[dst_offset..dst_offset + 4].copy_from_slice(&pixel.to_ne_bytes());
+                }
+            }
+        })
+    });
+}
+
+/// Returns the smallest area of an 8-bit indexed surface, like the ones
+/// rendered in *solid* or *shaded* mode, that holds every pixel not using
+/// the background color at index 0, or `None` if there is none.
+pub fn indexed_ink_bounds(surface: &SurfaceRef) -> Option<Rect> {
+    debug_assert_eq!(surface.pixel_format_enum(), PixelFormatEnum::Index8);
+    let (width, height) = surface.size();
+    let pitch = surface.pitch() as usize;
+    surface.with_lock(|pixels| {
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (width, height, 0, 0);
+        for y in 0..height {
+            let start = y as usize * pitch;
+            let row = &pixels[start..start + width as usize];
+            if let Some(first) = row.iter().position(|&index| index != 0) {
+                let last = row.iter().rposition(|&index| index != 0).unwrap_or(first);
+                min_x = min_x.min(first as u32);
+                max_x = max_x.max(last as u32);
+                min_y = min_y.min(y);
+                max_y = y;
+            }
+        }
+        if min_x > max_x {
+            None
+        } else {
+            Some(Rect::new(
+                min_x as i32,
+                min_y as i32,
+                max_x - min_x + 1,
+                max_y - min_y + 1,
+            ))
+        }
+    })
+}
+
+/// Extracts the alpha channel of an `ARGB8888` surface into an 8-bit
+/// indexed surface, whose palette maps each index to white with that alpha.
+pub fn alpha_mask<'a>(surface: &SurfaceRef) -> FontResult<Surface<'a>> {
+    debug_assert_eq!(surface.pixel_format_enum(), PixelFormatEnum::ARGB8888);
+    let (width, height) = surface.size();
+    let mut mask =
+        Surface::new(width, height, PixelFormatEnum::Index8).map_err(FontError::SdlError)?;
+    let ramp: Vec<Color> = (0..=255)
+        .map(|alpha| Color::RGBA(255, 255, 255, alpha))
+        .collect();
+    let palette = Palette::with_colors(&ramp).map_err(FontError::SdlError)?;
+    mask.set_palette(&palette).map_err(FontError::SdlError)?;
+
+    let src_pitch = surface.pitch() as usize;
+    let dst_pitch = mask.pitch() as usize;
+    surface.with_lock(|src| {
+        mask.with_lock_mut(|dst| {
+            for y in 0..height as usize {
+                for x in 0..width as usize {
+                    let offset = y * src_pitch + x * 4;
+                    let pixel = u32::from_ne_bytes([
+                        src[offset],
+                        src[offset + 1],
+                        src[offset + 2],
+                        src[offset + 3],
+                    ]);
+                    dst[y * dst_pitch + x] = (pixel >> 24) as u8;
+                }
+            }
+        })
+    });
+    Ok(mask)
+}
+
+/// Returns a copy of a rendered text surface recolored with the given color,
+/// to render text once in white and draw it in many colors.
+///
+/// Every channel of every pixel is multiplied by the matching channel of the
+/// color, alpha included, like color and alpha modulation do when blitting.
+/// This is meant for white text on a transparent background, like a
+/// *blended* rendering in white or a `PartialRendering::mask`, which it turns
+/// into the same text rendered in that color; other colors are darkened. The
+/// result is an `ARGB8888` surface, whatever the format of the input.
+pub fn tint_surface<'a, C: Into<Color>>(surface: &SurfaceRef, color: C) -> FontResult<Surface<'a>> {
+    let color = color.into();
+    let mut tinted = surface
+        .convert_format(PixelFormatEnum::ARGB8888)
+        .map_err(FontError::SdlError)?;
+    let scale = |pixel: u32, shift: u32, factor: u8| {
+        ((((pixel >> shift) & 0xff) * u32::from(factor) + 127) / 255) << shift
+    };
+    map_argb_pixels(&mut tinted, |_, _, pixel| {
+        scale(pixel, 24, color.a)
+            | scale(pixel, 16, color.r)
+            | scale(pixel, 8, color.g)
+            | scale(pixel, 0, color.b)
+    });
+    Ok(tinted)
+}
+
+/// Lays out rendered surfaces one after the other on a single line, aligned
+/// on a common baseline, for text mixing several fonts or sizes.
+///
+/// Each surface comes with the distance from its top to its baseline, which
+/// is `Font::ascent` for text rendered by `SDL2_TTF`. The result is an
+/// `ARGB8888` surface tall enough for the highest ascent and the deepest
+/// descent of the runs; it is transparent where no run was drawn.
+pub fn compose_baseline<'a>(runs: &[(Surface, i32)]) -> FontResult<Surface<'a>> {
+    let ascent = runs
+        .iter()
+        .map(|&(_, baseline)| baseline)
+        .max()
+        .unwrap_or(0);
+    let descent = runs
+        .iter()
+        .map(|(surface, baseline)| surface.height() as i32 - baseline)
+        .max()
+        .unwrap_or(0);
+    let width: u32 = runs.iter().map(|(surface, _)| surface.width()).sum();
+    let height = (ascent + descent).max(1) as u32;
+    let mut line = Surface::new(width.max(1), height, PixelFormatEnum::ARGB8888)
+        .map_err(FontError::SdlError)?;
+
+    let mut x = 0;
+    for (surface, baseline) in runs {
+        let y = ascent - baseline;
+        if surface.pixel_format_enum() == PixelFormatEnum::ARGB8888 {
+            draw_over(&mut line, surface, x, y);
+        } else {
+            let converted = surface
+                .convert_format(PixelFormatEnum::ARGB8888)
+                .map_err(FontError::SdlError)?;
+            draw_over(&mut line, &converted, x, y);
+        }
+        x += surface.width() as i32;
+    }
+    Ok(line)
+}
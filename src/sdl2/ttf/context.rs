@@ -1,17 +1,77 @@
+use common::read_to_end_limited;
 use get_error;
 use rwops::RWops;
 use std::error;
 use std::fmt;
+use std::fs::File;
 use std::io;
+use std::io::Read;
 use std::os::raw::{c_int, c_long};
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Mutex;
 use sys::ttf;
 use version::Version;
 
 use super::font::{
-    internal_load_font, internal_load_font_at_index, internal_load_font_from_ll, Font,
+    internal_load_font, internal_load_font_at_index, internal_load_font_from_buffer,
+    internal_load_font_from_ll, FaceInfo, Font,
 };
 
+/// The size faces are opened at when only their information is inspected.
+const FACE_INFO_POINT_SIZE: u16 = 12;
+
+lazy_static! {
+    static ref FONT_SEARCH_PATH: Mutex<Option<PathBuf>> = Mutex::new(None);
+}
+
+/// Sets a directory where fonts given by a bare file name, like
+/// `"DejaVuSans.ttf"`, are looked for by the loading functions of
+/// `Sdl2TtfContext`, such as an asset directory.
+///
+/// Fonts not found in that directory are still looked for relative to the
+/// current directory, and other paths, like absolute ones, are used as they
+/// are.
+pub fn set_font_search_path<P: AsRef<Path>>(dir: P) {
+    *FONT_SEARCH_PATH
+        .lock()
+        .unwrap_or_else(|err| err.into_inner()) = Some(dir.as_ref().to_path_buf());
+}
+
+/// Stops looking for fonts in the directory set with `set_font_search_path`.
+pub fn clear_font_search_path() {
+    *FONT_SEARCH_PATH
+        .lock()
+        .unwrap_or_else(|err| err.into_inner()) = None;
+}
+
+/// Returns the directory set with `set_font_search_path`, if any.
+pub fn font_search_path() -> Option<PathBuf> {
+    FONT_SEARCH_PATH
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .clone()
+}
+
+/// Returns the path of the given font file in the search path if it is a
+/// bare file name found there, or the path itself otherwise.
+fn resolve_font_path(path: &Path) -> PathBuf {
+    let mut components = path.components();
+    let is_bare_name = matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    );
+    if is_bare_name {
+        if let Some(dir) = font_search_path() {
+            let candidate = dir.join(path);
+            if candidate.is_file() {
+                return candidate;
+            }
+        }
+    }
+    path.to_path_buf()
+}
+
 /// A context manager for `SDL2_TTF` to manage C code initialization and clean-up.
 #[must_use]
 pub struct Sdl2TtfContext;
@@ -27,23 +87,102 @@ impl Drop for Sdl2TtfContext {
 
 impl Sdl2TtfContext {
     /// Loads a font from the given file with the given size in points.
+    ///
+    /// A bare file name is looked for in the directory set with
+    /// `set_font_search_path` first, like for the other loading functions
+    /// taking a path.
     pub fn load_font<'ttf, P: AsRef<Path>>(
         &'ttf self,
         path: P,
         point_size: u16,
     ) -> Result<Font<'ttf, 'static>, String> {
-        internal_load_font(path, point_size)
+        internal_load_font(resolve_font_path(path.as_ref()), point_size)
+    }
+
+    /// Loads a font from the file at the path `rel` relative to the
+    /// directory `base`, with the given size in points.
+    pub fn load_font_in<'ttf, B: AsRef<Path>, P: AsRef<Path>>(
+        &'ttf self,
+        base: B,
+        rel: P,
+        point_size: u16,
+    ) -> Result<Font<'ttf, 'static>, String> {
+        internal_load_font(base.as_ref().join(rel), point_size)
     }
 
     /// Loads the font at the given index of the file, with the given
     /// size in points.
+    ///
+    /// Returns a "face index out of range" error if the file has no face at
+    /// that index, see `Font::face_count`.
     pub fn load_font_at_index<'ttf, P: AsRef<Path>>(
         &'ttf self,
         path: P,
         index: u32,
         point_size: u16,
     ) -> Result<Font<'ttf, 'static>, String> {
-        internal_load_font_at_index(path, index, point_size)
+        internal_load_font_at_index(resolve_font_path(path.as_ref()), index, point_size)
+    }
+
+    /// Returns information about every face contained in the given font file,
+    /// such as the styles bundled in a TTC collection.
+    ///
+    /// Each face is opened in turn to be inspected, and closed again.
+    pub fn faces_info<P: AsRef<Path>>(&self, path: P) -> Result<Vec<FaceInfo>, String> {
+        let path = resolve_font_path(path.as_ref());
+        // the point size doesn't matter to the face names
+        let face_count = internal_load_font(&path, FACE_INFO_POINT_SIZE)?.face_count();
+        (0..face_count as u32)
+            .map(|index| {
+                let font = internal_load_font_at_index(&path, index, FACE_INFO_POINT_SIZE)?;
+                Ok(FaceInfo {
+                    index,
+                    family_name: font.face_family_name(),
+                    style_name: font.face_style_name(),
+                    is_fixed_width: font.face_is_fixed_width(),
+                })
+            })
+            .collect()
+    }
+
+    /// Loads a font from the given file once for each of the given sizes in
+    /// points.
+    ///
+    /// The file is only read once; all the returned fonts share the same
+    /// in-memory copy of the font data.
+    pub fn load_font_sizes<'ttf, P: AsRef<Path>>(
+        &'ttf self,
+        path: P,
+        point_sizes: &[u16],
+    ) -> Result<Vec<Font<'ttf, 'static>>, String> {
+        let path = resolve_font_path(path.as_ref());
+        let mut data = Vec::new();
+        File::open(&path)
+            .and_then(|mut file| file.read_to_end(&mut data))
+            .map_err(|err| format!("IO error: {}", err))?;
+        let buffer: Rc<[u8]> = data.into();
+        point_sizes
+            .iter()
+            .map(|&point_size| {
+                internal_load_font_from_buffer(Some(&path), buffer.clone(), 0, point_size)
+            })
+            .collect()
+    }
+
+    /// Loads a font from the data of the given reader, with the given size in
+    /// points.
+    ///
+    /// The data is read whole before the font is opened. Reading fails if
+    /// there is no data, or if there is more than `max_size` bytes, which
+    /// protects from endless streams.
+    pub fn load_font_from_reader<'ttf, R: Read>(
+        &'ttf self,
+        reader: R,
+        point_size: u16,
+        max_size: Option<usize>,
+    ) -> Result<Font<'ttf, 'static>, String> {
+        let data = read_to_end_limited(reader, max_size, "font")?;
+        internal_load_font_from_buffer(None, data.into(), 0, point_size)
     }
 
     /// Loads a font from the given SDL2 rwops object with the given size in
@@ -57,7 +196,7 @@ impl Sdl2TtfContext {
         if (raw as *mut ()).is_null() {
             Err(get_error())
         } else {
-            Ok(internal_load_font_from_ll(raw, Some(rwops)))
+            Ok(internal_load_font_from_ll(raw, Some(rwops), 0, point_size))
         }
     }
 
@@ -75,7 +214,12 @@ impl Sdl2TtfContext {
         if (raw as *mut ()).is_null() {
             Err(get_error())
         } else {
-            Ok(internal_load_font_from_ll(raw, Some(rwops)))
+            Ok(internal_load_font_from_ll(
+                raw,
+                Some(rwops),
+                index,
+                point_size,
+            ))
         }
     }
 }
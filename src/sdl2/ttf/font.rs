@@ -1,6 +1,10 @@
+use common::path_to_cstring;
 use get_error;
-use pixels::Color;
+use pixels::{Color, PixelFormatEnum};
+use rect::Rect;
 use rwops::RWops;
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::error;
 use std::error::Error;
 use std::ffi::NulError;
@@ -8,13 +12,36 @@ use std::ffi::{CStr, CString};
 use std::fmt;
 use std::marker::PhantomData;
 use std::os::raw::{c_int, c_long, c_uint};
-use std::path::Path;
-use surface::Surface;
+use std::path::{Path, PathBuf};
+use std::ptr;
+use std::rc::Rc;
+use std::slice;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use surface::{Surface, SurfaceRef};
 use sys::ttf;
-use sys::SDL_Surface;
+use sys::{SDL_Color, SDL_Surface};
+#[cfg(feature = "unicode-normalization")]
+use unicode_normalization::{is_nfc, UnicodeNormalization};
+
+use super::compose::{
+    alpha_mask, crop_surface, draw_over, indexed_ink_bounds, map_argb_pixels, premultiply_pixel,
+    rotate_quarter_turns,
+};
+use super::report::FontReport;
 
 bitflags! {
     /// The styling of a font.
+    ///
+    /// Styles combine with `|`, or with `FontStyle::combine` when they are
+    /// chosen at runtime:
+    ///
+    /// ```no_run
+    /// use sdl2::ttf::FontStyle;
+    ///
+    /// let style = FontStyle::BOLD | FontStyle::ITALIC;
+    /// assert_eq!(style, FontStyle::combine(&[FontStyle::BOLD, FontStyle::ITALIC]));
+    /// ```
     pub struct FontStyle: i32 {
         const NORMAL        = ttf::TTF_STYLE_NORMAL as i32;
         const BOLD          = ttf::TTF_STYLE_BOLD as i32;
@@ -24,6 +51,15 @@ bitflags! {
     }
 }
 
+impl FontStyle {
+    /// Returns the union of the given styles, or `NORMAL` if there are none.
+    pub fn combine(styles: &[FontStyle]) -> FontStyle {
+        styles
+            .iter()
+            .fold(FontStyle::NORMAL, |combined, &style| combined | style)
+    }
+}
+
 /// Information about the hinting of a font.
 /// See [wikipedia](https://en.wikipedia.org/wiki/Font_hinting)
 #[repr(i32)]
@@ -45,6 +81,29 @@ pub struct GlyphMetrics {
     pub advance: i32,
 }
 
+/// A glyph rendered on its own, with where to blit it relative to the pen.
+/// See `Font::glyph_blit_info`.
+pub struct GlyphBlit {
+    /// The glyph rendered in *blended* mode, cropped to its bounding box.
+    pub surface: Surface<'static>,
+    /// The offset from the pen position to the left of the surface, that is
+    /// the left bearing of the glyph.
+    pub x_offset: i32,
+    /// The offset from the top of the line to the top of the surface.
+    pub y_offset: i32,
+    /// How far to move the pen for the next glyph.
+    pub advance: i32,
+}
+
+/// Information about one of the faces of a font file.
+#[derive(Debug, PartialEq, Clone)]
+pub struct FaceInfo {
+    pub index: u32,
+    pub family_name: Option<String>,
+    pub style_name: Option<String>,
+    pub is_fixed_width: bool,
+}
+
 /// The result of an `SDL2_TTF` font operation.
 pub type FontResult<T> = Result<T, FontError>;
 
@@ -53,6 +112,8 @@ pub type FontResult<T> = Result<T, FontError>;
 pub enum FontError {
     /// A Latin-1 encoded byte string is invalid.
     InvalidLatin1Text(NulError),
+    /// A UTF-8 string contains a nul character, which SDL2_TTF can't render.
+    InvalidUtf8Text(NulError),
     /// A SDL2-related error occured.
     SdlError(String),
 }
@@ -61,6 +122,7 @@ impl error::Error for FontError {
     fn description(&self) -> &str {
         match *self {
             FontError::InvalidLatin1Text(ref error) => error.description(),
+            FontError::InvalidUtf8Text(ref error) => error.description(),
             FontError::SdlError(ref message) => message,
         }
     }
@@ -68,6 +130,7 @@ impl error::Error for FontError {
     fn cause(&self) -> Option<&dyn error::Error> {
         match *self {
             FontError::InvalidLatin1Text(ref error) => Some(error),
+            FontError::InvalidUtf8Text(ref error) => Some(error),
             FontError::SdlError(_) => None,
         }
     }
@@ -79,6 +142,9 @@ impl fmt::Display for FontError {
             FontError::InvalidLatin1Text(ref err) => {
                 write!(f, "Invalid Latin-1 bytes: {}", err.description())
             }
+            FontError::InvalidUtf8Text(ref err) => {
+                write!(f, "Invalid UTF-8 text: {}", err.description())
+            }
             FontError::SdlError(ref msg) => {
                 write!(f, "SDL2 error: {}", msg)
             }
@@ -94,26 +160,182 @@ enum RenderableText<'a> {
 }
 impl<'a> RenderableText<'a> {
     /// Converts the given text to a c-style string if possible.
+    ///
+    /// `SDL2_TTF` would stop at the first nul character, silently dropping
+    /// the rest of the text, so text containing one is an error instead.
     fn convert(&self) -> FontResult<CString> {
         match *self {
-            RenderableText::Utf8(text) => Ok(CString::new(text).unwrap()),
+            RenderableText::Utf8(text) => CString::new(text).map_err(FontError::InvalidUtf8Text),
             RenderableText::Latin1(bytes) => match CString::new(bytes) {
                 Err(err) => Err(FontError::InvalidLatin1Text(err)),
                 Ok(cstring) => Ok(cstring),
             },
-            RenderableText::Char(ref string) => Ok(CString::new(string.as_bytes()).unwrap()),
+            RenderableText::Char(ref string) => {
+                CString::new(string.as_bytes()).map_err(FontError::InvalidUtf8Text)
+            }
+        }
+    }
+
+    /// Returns the text as a string, for layouts placing its characters one
+    /// at a time. Text with a nul is rejected whole, as by `convert`.
+    fn decoded(&self) -> FontResult<String> {
+        self.convert()?;
+        Ok(match *self {
+            RenderableText::Utf8(text) => text.to_owned(),
+            RenderableText::Latin1(bytes) => bytes.iter().map(|&byte| byte as char).collect(),
+            RenderableText::Char(ref string) => string.clone(),
+        })
+    }
+
+    /// Returns `true` if there is no text to render.
+    fn is_empty(&self) -> bool {
+        match *self {
+            RenderableText::Utf8(text) => text.is_empty(),
+            RenderableText::Latin1(bytes) => bytes.is_empty(),
+            RenderableText::Char(_) => false,
+        }
+    }
+}
+
+/// The ways text can be rendered, to choose one at runtime with
+/// `PartialRendering::with_mode`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RenderMode {
+    /// See `PartialRendering::solid`.
+    Solid,
+    /// See `PartialRendering::shaded`. The alpha of the background color is
+    /// ignored.
+    Shaded { background: Color },
+    /// See `PartialRendering::blended`.
+    Blended,
+}
+
+/// How crisp or smooth text rendered with `Font::render_str` is, to switch a
+/// whole user interface between pixel-perfect and anti-aliased text with a
+/// single setting. See `Font::set_render_quality`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RenderQuality {
+    /// *Solid* rendering, without anti-aliasing.
+    Fast,
+    /// *Shaded* rendering, anti-aliased over an opaque black background.
+    Smooth,
+    /// *Blended* rendering, anti-aliased over a transparent background.
+    High,
+}
+
+/// Whether the color channels of *blended* renderings are premultiplied by
+/// their alpha. See `Font::set_output_alpha`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AlphaMode {
+    /// The color channels hold the color of the text, as `SDL2_TTF` renders
+    /// them, which is what SDL blits and textures expect by default.
+    Straight,
+    /// The color channels are multiplied by the alpha, for compositing
+    /// pipelines and GPU blending set up for premultiplied alpha.
+    Premultiplied,
+}
+
+/// The Unicode normalization applied to text by a font before measuring or
+/// rendering it. See `Font::set_normalization`.
+#[cfg(feature = "unicode-normalization")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NormalizationForm {
+    /// The text is used as it is.
+    None,
+    /// The text is put in Normalization Form C, composing characters with
+    /// their combining marks where possible, like `"e\u{301}"` into `"é"`.
+    Nfc,
+}
+
+/// What a font was opened from, identifying it for caches: unlike the
+/// address of the C font object, it isn't reused by another font once the
+/// font is dropped.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(super) enum FontSource {
+    File {
+        path: PathBuf,
+        point_size: u16,
+        index: u32,
+    },
+    /// A font loaded from an `RWops`, by its unique id.
+    Loaded(u64),
+}
+
+/// The id of the next font loaded, see `FontSource::Loaded`.
+static NEXT_FONT_ID: AtomicU64 = AtomicU64::new(0);
+
+fn next_font_id() -> u64 {
+    NEXT_FONT_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// How many converted texts a font keeps for `Font::c_text` before they are
+/// all dropped.
+const C_STRING_CACHE_CAPACITY: usize = 64;
+
+/// The default point size from which `Font::auto_render_str` renders in
+/// *blended* mode rather than *solid* mode.
+const DEFAULT_SOLID_BELOW: u16 = 12;
+
+impl RenderQuality {
+    fn mode(self) -> RenderMode {
+        match self {
+            RenderQuality::Fast => RenderMode::Solid,
+            RenderQuality::Smooth => RenderMode::Shaded {
+                background: Color::BLACK,
+            },
+            RenderQuality::High => RenderMode::Blended,
         }
     }
 }
 
 /// A builder for a font rendering.
+///
+/// Colors are taken as anything that converts into a `pixels::Color`: a
+/// `Color` itself, or `(r, g, b)` and `(r, g, b, a)` tuples of `u8`.
+///
+/// ```no_run
+/// use sdl2::pixels::Color;
+///
+/// let ttf_context = sdl2::ttf::init().unwrap();
+/// let font = ttf_context.load_font("font.ttf", 16).unwrap();
+/// let red = font.render("Hello").blended(Color::RGB(255, 0, 0)).unwrap();
+/// let same = font.render("Hello").blended((255, 0, 0)).unwrap();
+/// let label = font
+///     .render("Hello")
+///     .shaded(Color::WHITE, Color::RGBA(0, 0, 64, 255))
+///     .unwrap();
+/// ```
+///
+/// Whitespace, like indentation, takes its width in every mode but draws
+/// nothing over the background: a string of spaces gives a fully
+/// transparent surface in *blended* mode, a surface of the keyed background
+/// color in *solid* mode, which stays invisible when blitted, and a bar of
+/// the opaque background color in *shaded* mode. The `UNDERLINE` and
+/// `STRIKETHROUGH` styles still draw their lines under whitespace.
 #[must_use]
 pub struct PartialRendering<'f, 'text> {
     text: RenderableText<'text>,
     font: &'f Font<'f, 'f>,
 }
 
-/// Converts the given raw pointer to a surface.
+/// Colors a pixel of white text rendered in *blended* mode with the color
+/// found at `t`, between 0 and 1, of the gradient from `from` to `to`.
+fn gradient_pixel(pixel: u32, from: Color, to: Color, t: f32) -> u32 {
+    let mix = |a: u8, b: u8| (f32::from(a) + (f32::from(b) - f32::from(a)) * t).round() as u32;
+    let alpha = ((pixel >> 24) * mix(from.a, to.a) + 127) / 255;
+    (alpha << 24) | (mix(from.r, to.r) << 16) | (mix(from.g, to.g) << 8) | mix(from.b, to.b)
+}
+
+/// Returns the index of the caret position nearest to `x`.
+fn nearest_caret(positions: &[i32], x: i32) -> usize {
+    positions
+        .iter()
+        .enumerate()
+        .min_by_key(|&(_, position)| (position - x).abs())
+        .map_or(0, |(index, _)| index)
+}
+
+/// Converts the given raw pointer to a surface, which takes ownership of it.
 fn convert_to_surface<'a>(raw: *mut SDL_Surface) -> FontResult<Surface<'a>> {
     if (raw as *mut ()).is_null() {
         Err(FontError::SdlError(get_error()))
@@ -122,56 +344,138 @@ fn convert_to_surface<'a>(raw: *mut SDL_Surface) -> FontResult<Surface<'a>> {
     }
 }
 
+/// Drops the alpha of the background of a *shaded* rendering, which
+/// SDL2_TTF would otherwise copy into the palette of the surface and make
+/// the background transparent when blitted.
+fn opaque(background: Color) -> SDL_Color {
+    let (r, g, b) = background.rgb();
+    Color::RGB(r, g, b).into()
+}
+
+/// Creates the surface returned when rendering empty text: a fully
+/// transparent `ARGB8888` surface one pixel wide and as tall as the font, so
+/// that it can still be turned into a texture or laid out on a line.
+fn empty_text_surface<'a>(font: &Font) -> FontResult<Surface<'a>> {
+    let height = font.height().max(1) as u32;
+    // new surfaces are zero-filled, which is transparent black
+    Surface::new(1, height, PixelFormatEnum::ARGB8888).map_err(FontError::SdlError)
+}
+
 impl<'f, 'text> PartialRendering<'f, 'text> {
-    /// Renders the text in *solid* mode.
+    /// Renders the text in the given mode, which can be chosen at runtime.
     /// See [the SDL2_TTF docs](https://www.libsdl.org/projects/SDL_ttf/docs/SDL_ttf.html#SEC42)
-    /// for an explanation.
-    pub fn solid<'b, T>(self, color: T) -> FontResult<Surface<'b>>
+    /// for an explanation of the modes.
+    ///
+    /// *Blended* renderings follow the output alpha of the font, see
+    /// `Font::set_output_alpha`.
+    pub fn with_mode<'b, T>(self, color: T, mode: RenderMode) -> FontResult<Surface<'b>>
     where
         T: Into<Color>,
     {
-        let source = self.text.convert()?;
-        let color = color.into().into();
+        let font = self.font;
+        let surface = self.render_straight(color.into(), mode)?;
+        Ok(match mode {
+            RenderMode::Blended => font.apply_output_alpha(surface),
+            _ => surface,
+        })
+    }
+
+    /// Renders the text in *blended* mode with straight alpha, whatever the
+    /// output alpha of the font, for renderings composed of several others.
+    pub(super) fn blended_straight<'b>(self, color: Color) -> FontResult<Surface<'b>> {
+        self.render_straight(color, RenderMode::Blended)
+    }
+
+    fn render_straight<'b>(self, color: Color, mode: RenderMode) -> FontResult<Surface<'b>> {
+        if self.text.is_empty() {
+            return empty_text_surface(self.font);
+        }
+        let source = self.font.c_text(&self.text)?;
+        let text = source.as_ptr();
+        let foreground = color.into();
+        let utf8 = match self.text {
+            RenderableText::Utf8(_) | RenderableText::Char(_) => true,
+            RenderableText::Latin1(_) => false,
+        };
         let raw = unsafe {
-            match self.text {
-                RenderableText::Utf8(_) | RenderableText::Char(_) => {
-                    ttf::TTF_RenderUTF8_Solid(self.font.raw(), source.as_ptr(), color)
+            let font = self.font.raw();
+            match (mode, utf8) {
+                (RenderMode::Solid, true) => ttf::TTF_RenderUTF8_Solid(font, text, foreground),
+                (RenderMode::Solid, false) => ttf::TTF_RenderText_Solid(font, text, foreground),
+                (RenderMode::Shaded { background }, true) => {
+                    ttf::TTF_RenderUTF8_Shaded(font, text, foreground, opaque(background))
                 }
-                RenderableText::Latin1(_) => {
-                    ttf::TTF_RenderText_Solid(self.font.raw(), source.as_ptr(), color)
+                (RenderMode::Shaded { background }, false) => {
+                    ttf::TTF_RenderText_Shaded(font, text, foreground, opaque(background))
                 }
+                (RenderMode::Blended, true) => ttf::TTF_RenderUTF8_Blended(font, text, foreground),
+                (RenderMode::Blended, false) => ttf::TTF_RenderText_Blended(font, text, foreground),
             }
         };
         convert_to_surface(raw)
     }
 
+    /// Renders the text in *solid* mode.
+    /// See [the SDL2_TTF docs](https://www.libsdl.org/projects/SDL_ttf/docs/SDL_ttf.html#SEC42)
+    /// for an explanation.
+    pub fn solid<'b, T>(self, color: T) -> FontResult<Surface<'b>>
+    where
+        T: Into<Color>,
+    {
+        self.with_mode(color, RenderMode::Solid)
+    }
+
+    /// Renders the text in *solid* mode, with the background keyed out so
+    /// that the surface can be blitted over other content without paying
+    /// for *blended* mode.
+    pub fn solid_keyed<'b, T>(self, color: T) -> FontResult<Surface<'b>>
+    where
+        T: Into<Color>,
+    {
+        let color = color.into();
+        let mut surface = self.solid(color)?;
+        // SDL2_TTF fills palette index 0 (the background) with the inverse
+        // of the foreground color
+        let (r, g, b) = color.rgb();
+        let background = Color::RGB(255 - r, 255 - g, 255 - b);
+        surface
+            .set_color_key(true, background)
+            .map_err(FontError::SdlError)?;
+        Ok(surface)
+    }
+
     /// Renders the text in *shaded* mode.
     /// See [the SDL2_TTF docs](https://www.libsdl.org/projects/SDL_ttf/docs/SDL_ttf.html#SEC42)
     /// for an explanation.
+    ///
+    /// The background is always opaque: the alpha of the background color
+    /// is ignored.
     pub fn shaded<'b, T>(self, color: T, background: T) -> FontResult<Surface<'b>>
     where
         T: Into<Color>,
     {
-        let source = self.text.convert()?;
-        let foreground = color.into().into();
-        let background = background.into().into();
-        let raw = unsafe {
-            match self.text {
-                RenderableText::Utf8(_) | RenderableText::Char(_) => ttf::TTF_RenderUTF8_Shaded(
-                    self.font.raw(),
-                    source.as_ptr(),
-                    foreground,
-                    background,
-                ),
-                RenderableText::Latin1(_) => ttf::TTF_RenderText_Shaded(
-                    self.font.raw(),
-                    source.as_ptr(),
-                    foreground,
-                    background,
-                ),
-            }
-        };
-        convert_to_surface(raw)
+        let background = background.into();
+        self.with_mode(color, RenderMode::Shaded { background })
+    }
+
+    /// Renders the text in *shaded* mode, then crops the result to the
+    /// pixels actually drawn by the glyphs, so that the background color
+    /// does not fill the whole line box around the text.
+    ///
+    /// Text that draws nothing, like spaces, is left uncropped.
+    pub fn shaded_trimmed<'b, T>(self, color: T, background: T) -> FontResult<Surface<'b>>
+    where
+        T: Into<Color>,
+    {
+        let surface = self.shaded(color, background)?;
+        if surface.pixel_format_enum() != PixelFormatEnum::Index8 {
+            // empty text is rendered as a transparent surface
+            return Ok(surface);
+        }
+        match indexed_ink_bounds(&surface) {
+            Some(rect) => crop_surface(&surface, rect),
+            None => Ok(surface),
+        }
     }
 
     /// Renders the text in *blended* mode.
@@ -181,19 +485,443 @@ impl<'f, 'text> PartialRendering<'f, 'text> {
     where
         T: Into<Color>,
     {
-        let source = self.text.convert()?;
-        let color = color.into().into();
-        let raw = unsafe {
-            match self.text {
-                RenderableText::Utf8(_) | RenderableText::Char(_) => {
-                    ttf::TTF_RenderUTF8_Blended(self.font.raw(), source.as_ptr(), color)
-                }
-                RenderableText::Latin1(_) => {
-                    ttf::TTF_RenderText_Blended(self.font.raw(), source.as_ptr(), color)
-                }
+        self.with_mode(color, RenderMode::Blended)
+    }
+
+    /// Renders the text in *solid* mode with the default color of the font.
+    /// See `Font::set_default_color`.
+    pub fn solid_default<'b>(self) -> FontResult<Surface<'b>> {
+        let color = self.font.default_color;
+        self.solid(color)
+    }
+
+    /// Renders the text in *shaded* mode with the default color of the font
+    /// over the given background. See `Font::set_default_color`.
+    pub fn shaded_default<'b, T>(self, background: T) -> FontResult<Surface<'b>>
+    where
+        T: Into<Color>,
+    {
+        let color = self.font.default_color;
+        self.shaded(color, background.into())
+    }
+
+    /// Renders the text in *blended* mode with the default color of the
+    /// font. See `Font::set_default_color`.
+    pub fn blended_default<'b>(self) -> FontResult<Surface<'b>> {
+        let color = self.font.default_color;
+        self.blended(color)
+    }
+
+    /// Renders the text in *blended* mode and saves the result to a BMP
+    /// file, for instance to generate test fixtures.
+    pub fn blended_to_bmp<T, P>(self, color: T, path: P) -> FontResult<()>
+    where
+        T: Into<Color>,
+        P: AsRef<Path>,
+    {
+        self.blended(color)?
+            .save_bmp(path)
+            .map_err(FontError::SdlError)
+    }
+
+    /// Renders the text in *blended* mode, with the color channels of the
+    /// result premultiplied by alpha.
+    ///
+    /// This is meant for compositing pipelines that expect premultiplied
+    /// alpha textures. The output alpha of the font is ignored.
+    pub fn blended_premultiplied<'b, T>(self, color: T) -> FontResult<Surface<'b>>
+    where
+        T: Into<Color>,
+    {
+        let mut surface = self.blended_straight(color.into())?;
+        map_argb_pixels(&mut surface, |_, _, pixel| premultiply_pixel(pixel));
+        Ok(surface)
+    }
+
+    /// Renders the coverage of the text in *blended* mode, without color.
+    ///
+    /// The result is an 8-bit `Index8` surface where each pixel holds the
+    /// alpha of the text, from 0 (transparent) to 255 (opaque). Its palette
+    /// maps each index to white with that alpha, so that it still blits as
+    /// white text. This is meant for GPU text colored in a shader, uploading
+    /// a quarter of the data of a *blended* rendering.
+    pub fn mask<'b>(self) -> FontResult<Surface<'b>> {
+        let surface = self.blended_straight(Color::WHITE)?;
+        alpha_mask(&surface)
+    }
+
+    /// Renders the text in *blended* mode and returns its pixels as tightly
+    /// packed RGBA bytes, one byte per channel in that order, along with the
+    /// width and height of the image.
+    ///
+    /// This is meant for uploading text to renderers other than SDL's, which
+    /// expect rows without the padding of SDL surfaces.
+    pub fn blended_rgba_bytes<T>(self, color: T) -> FontResult<(Vec<u8>, u32, u32)>
+    where
+        T: Into<Color>,
+    {
+        let surface = self
+            .blended(color)?
+            .convert_format(PixelFormatEnum::RGBA32)
+            .map_err(FontError::SdlError)?;
+        let (width, height) = surface.size();
+        let pitch = surface.pitch() as usize;
+        let row_len = width as usize * 4;
+        let mut bytes = Vec::with_capacity(row_len * height as usize);
+        surface.with_lock(|pixels| {
+            for y in 0..height as usize {
+                bytes.extend_from_slice(&pixels[y * pitch..y * pitch + row_len]);
+            }
+        });
+        Ok((bytes, width, height))
+    }
+
+    /// Renders the text in *blended* mode with an underline of the given
+    /// thickness in pixels, drawn `offset` pixels below the baseline, or
+    /// half the descent below it if `None`.
+    ///
+    /// This gives control over the underline that the `UNDERLINE` style
+    /// does not, which may be too thin at small sizes. That style is ignored
+    /// for this rendering. Parts of the line falling outside the surface are
+    /// clipped.
+    pub fn blended_underlined<'b, T>(
+        self,
+        color: T,
+        thickness: u32,
+        offset: Option<i32>,
+    ) -> FontResult<Surface<'b>>
+    where
+        T: Into<Color>,
+    {
+        let color = color.into();
+        let font = self.font;
+        let style = font.get_style();
+        // the underline style does not change glyphs, so toggling it does not
+        // flush the glyph cache of SDL2_TTF
+        if style.contains(FontStyle::UNDERLINE) {
+            let plain = style - FontStyle::UNDERLINE;
+            unsafe { ttf::TTF_SetFontStyle(font.raw(), plain.bits() as c_int) };
+        }
+        let surface = self.blended_straight(color);
+        unsafe { ttf::TTF_SetFontStyle(font.raw(), style.bits() as c_int) };
+        let mut surface = surface?;
+
+        let top = font.ascent() + offset.unwrap_or(-font.descent() / 2);
+        let bottom = top + thickness as i32;
+        let line = (u32::from(color.a) << 24)
+            | (u32::from(color.r) << 16)
+            | (u32::from(color.g) << 8)
+            | u32::from(color.b);
+        map_argb_pixels(&mut surface, |_, y, pixel| {
+            if (y as i32) >= top && (y as i32) < bottom {
+                line
+            } else {
+                pixel
             }
+        });
+        Ok(font.apply_output_alpha(surface))
+    }
+
+    /// Renders the text in *blended* mode, clipping the result to the given
+    /// maximum width instead of wrapping it.
+    ///
+    /// The returned flag is `true` if the text did not fit and was clipped.
+    pub fn blended_clipped<'b, T>(self, color: T, max_width: u32) -> FontResult<(Surface<'b>, bool)>
+    where
+        T: Into<Color>,
+    {
+        let surface = self.blended(color)?;
+        if surface.width() <= max_width {
+            return Ok((surface, false));
+        }
+        let rect = Rect::new(0, 0, max_width, surface.height());
+        Ok((crop_surface(&surface, rect)?, true))
+    }
+
+    /// Renders the text in *blended* mode, centered horizontally and
+    /// vertically on a transparent surface of the given size. Text larger
+    /// than the surface is clipped on both sides.
+    pub fn blended_centered<'b, T>(
+        self,
+        color: T,
+        width: u32,
+        height: u32,
+    ) -> FontResult<Surface<'b>>
+    where
+        T: Into<Color>,
+    {
+        let font = self.font;
+        let text = self.blended_straight(color.into())?;
+        let mut surface =
+            Surface::new(width, height, PixelFormatEnum::ARGB8888).map_err(FontError::SdlError)?;
+        let x = (width as i32 - text.width() as i32) / 2;
+        let y = (height as i32 - text.height() as i32) / 2;
+        draw_over(&mut surface, &text, x, y);
+        Ok(font.apply_output_alpha(surface))
+    }
+
+    /// Renders the text in *blended* mode at `scale` times the size of the
+    /// font, for HiDPI displays: the surface is meant to be drawn into a
+    /// rectangle `scale` times smaller, which keeps the text sharp.
+    ///
+    /// `SDL2_TTF` can't resize a font, so the face is opened again at the
+    /// scaled size with the same style, hinting and kerning, and a scaled
+    /// outline. This fails for fonts opened from an `RWops`.
+    pub fn blended_scaled<'b, T>(self, color: T, scale: u32) -> FontResult<Surface<'b>>
+    where
+        T: Into<Color>,
+    {
+        if scale == 1 {
+            return self.blended(color);
+        }
+        let font = self.font;
+        let point_size = u32::from(font.point_size()) * scale;
+        if point_size == 0 || point_size > u32::from(u16::MAX) {
+            return Err(FontError::SdlError(format!("Invalid scale: {}", scale)));
+        }
+        let mut scaled = font
+            .open_again(point_size as u16)
+            .map_err(FontError::SdlError)?;
+        scaled.set_style(font.get_style());
+        scaled.set_hinting(font.get_hinting());
+        scaled.set_kerning(font.get_kerning());
+        scaled.substitute = font.substitute;
+        scaled.output_alpha = font.output_alpha;
+        let outline = u32::from(font.get_outline_width()) * scale;
+        scaled.set_outline_width(outline.min(u32::from(u16::MAX)) as u16);
+        let rendering = PartialRendering {
+            text: self.text,
+            font: &scaled,
         };
-        convert_to_surface(raw)
+        rendering.blended(color)
+    }
+
+    /// Renders the text in *blended* mode wrapped and justified to exactly
+    /// the given width, for book-like layout.
+    ///
+    /// Words are separated by whitespace and lines by `\n`. The space
+    /// between the words of every line is stretched so that the line fills
+    /// the width, except for the last line of each paragraph and lines
+    /// holding a single word, which are aligned left. Words wider than the
+    /// surface are clipped. Lines are `recommended_line_spacing` pixels
+    /// apart, or `height` if larger.
+    pub fn blended_justified<'b, T>(self, color: T, width: u32) -> FontResult<Surface<'b>>
+    where
+        T: Into<Color>,
+    {
+        let text = self.text.decoded()?;
+        let color = color.into();
+        let font = self.font;
+        let width = width.max(1) as i32;
+        let (space, _) = font.size_of(" ")?;
+        let space = space as i32;
+
+        // each line holds its words with their widths, and whether it ends
+        // a paragraph
+        let mut lines: Vec<(Vec<(&str, i32)>, bool)> = Vec::new();
+        for paragraph in text.split('\n') {
+            let mut line: Vec<(&str, i32)> = Vec::new();
+            let mut line_width = 0;
+            for word in paragraph.split_whitespace() {
+                let word_width = font.size_of(word)?.0 as i32;
+                if !line.is_empty() && line_width + space + word_width > width {
+                    lines.push((line, false));
+                    line = Vec::new();
+                }
+                line_width = if line.is_empty() {
+                    word_width
+                } else {
+                    line_width + space + word_width
+                };
+                line.push((word, word_width));
+            }
+            lines.push((line, true));
+        }
+
+        let line_skip = font.recommended_line_spacing().max(font.height());
+        let height = (line_skip * lines.len() as i32).max(1);
+        let mut surface = Surface::new(width as u32, height as u32, PixelFormatEnum::ARGB8888)
+            .map_err(FontError::SdlError)?;
+        for (row, (words, ends_paragraph)) in lines.iter().enumerate() {
+            let gaps = words.len().saturating_sub(1) as i32;
+            let words_width: i32 = words.iter().map(|&(_, word_width)| word_width).sum();
+            let (gap, extra) = if *ends_paragraph || gaps == 0 {
+                (space, 0)
+            } else {
+                let stretch = (width - words_width).max(0);
+                (stretch / gaps, stretch % gaps)
+            };
+            let mut x = 0;
+            for (index, &(word, word_width)) in words.iter().enumerate() {
+                let rendered = font.render(word).blended_straight(color)?;
+                draw_over(&mut surface, &rendered, x, row as i32 * line_skip);
+                // the remaining pixels widen the first gaps by one
+                x += word_width + gap + if (index as i32) < extra { 1 } else { 0 };
+            }
+        }
+        Ok(font.apply_output_alpha(surface))
+    }
+
+    /// Renders the text in *blended* mode, rotated clockwise by the given
+    /// number of quarter turns, for side labels of charts or vertical tabs.
+    ///
+    /// `SDL2` surfaces can't be rotated, so the rendered pixels are moved
+    /// around, which keeps them exact. One turn reads from top to bottom and
+    /// three turns from bottom to top.
+    pub fn blended_rotated<'b, T>(self, color: T, quarter_turns: u8) -> FontResult<Surface<'b>>
+    where
+        T: Into<Color>,
+    {
+        let surface = self.blended(color)?;
+        if quarter_turns.is_multiple_of(4) {
+            return Ok(surface);
+        }
+        rotate_quarter_turns(&surface, quarter_turns)
+    }
+
+    /// Renders the text in *blended* mode filled with a vertical gradient,
+    /// from the `top` color on the first row of the surface to the `bottom`
+    /// color on the last one, for title text.
+    ///
+    /// The anti-aliasing of the glyphs is kept, multiplied by the alpha of
+    /// the gradient. The gradient spans the whole line of the font, ascent
+    /// and descent included, rather than the ink of each glyph.
+    pub fn blended_gradient<'b, T, U>(self, top: T, bottom: U) -> FontResult<Surface<'b>>
+    where
+        T: Into<Color>,
+        U: Into<Color>,
+    {
+        let (top, bottom) = (top.into(), bottom.into());
+        let font = self.font;
+        let mut surface = self.blended_straight(Color::WHITE)?;
+        let span = surface.height().saturating_sub(1).max(1) as f32;
+        map_argb_pixels(&mut surface, |_, y, pixel| {
+            gradient_pixel(pixel, top, bottom, y as f32 / span)
+        });
+        Ok(font.apply_output_alpha(surface))
+    }
+
+    /// Renders the text in *blended* mode filled with a horizontal gradient,
+    /// from the `left` color on the first column of the surface to the
+    /// `right` color on the last one. See `blended_gradient`.
+    pub fn blended_gradient_horizontal<'b, T, U>(self, left: T, right: U) -> FontResult<Surface<'b>>
+    where
+        T: Into<Color>,
+        U: Into<Color>,
+    {
+        let (left, right) = (left.into(), right.into());
+        let font = self.font;
+        let mut surface = self.blended_straight(Color::WHITE)?;
+        let span = surface.width().saturating_sub(1).max(1) as f32;
+        map_argb_pixels(&mut surface, |x, _, pixel| {
+            gradient_pixel(pixel, left, right, x as f32 / span)
+        });
+        Ok(font.apply_output_alpha(surface))
+    }
+
+    /// Renders the text in *blended* mode and blends it onto the target
+    /// surface, with the left of the text at `x` and its baseline at
+    /// `baseline_y`, for drawing lines straight into a console or log
+    /// backbuffer.
+    ///
+    /// The top of the text is `ascent` pixels above the baseline. Whatever
+    /// falls outside of the target, or of its clipping rectangle, is left
+    /// out. The text is blended with straight alpha, whatever the output
+    /// alpha of the font.
+    pub fn blended_at<T>(
+        self,
+        color: T,
+        target: &mut SurfaceRef,
+        x: i32,
+        baseline_y: i32,
+    ) -> FontResult<()>
+    where
+        T: Into<Color>,
+    {
+        let top = baseline_y - self.font.ascent();
+        let surface = self.blended_straight(color.into())?;
+        let dst = Rect::new(x, top, surface.width(), surface.height());
+        surface
+            .blit(None, target, dst)
+            .map_err(FontError::SdlError)?;
+        Ok(())
+    }
+
+    /// Renders the text in *blended* mode with `extra_px` pixels added
+    /// between characters, or removed if negative, for letter-spacing.
+    ///
+    /// Each glyph is rendered on its own and placed `extra_px` pixels further
+    /// than its advance, so kerning is not applied. Negative tracking can't
+    /// bring a character closer than half its advance to the previous one.
+    /// The surface has the size given by `Font::size_of_tracked`.
+    pub fn blended_tracked<'b, T>(self, color: T, extra_px: i32) -> FontResult<Surface<'b>>
+    where
+        T: Into<Color>,
+    {
+        if self.text.is_empty() {
+            return empty_text_surface(self.font);
+        }
+        let chars: Vec<char> = self.text.decoded()?.chars().collect();
+        let color = color.into();
+        let font = self.font;
+        let (pens, width) = font.tracked_layout(&chars, extra_px)?;
+        let mut surface = Surface::new(
+            width.max(1) as u32,
+            font.height().max(1) as u32,
+            PixelFormatEnum::ARGB8888,
+        )
+        .map_err(FontError::SdlError)?;
+        for (&ch, pen) in chars.iter().zip(pens) {
+            let glyph = font.glyph_blit_info(font.substitute_for(ch), color)?;
+            draw_over(
+                &mut surface,
+                &glyph.surface,
+                pen + glyph.x_offset,
+                glyph.y_offset,
+            );
+        }
+        Ok(font.apply_output_alpha(surface))
+    }
+
+    /// Renders the text in *blended* mode with the characters stacked from
+    /// top to bottom, for vertical writing such as CJK text.
+    ///
+    /// Each character takes a line of the font, as tall as the larger of
+    /// `recommended_line_spacing` and `height`, and is centered on a column
+    /// as wide as the widest advance, so half-width characters are centered
+    /// on full-width ones.
+    /// Glyphs are not rotated and kerning is not applied.
+    pub fn blended_vertical<'b, T>(self, color: T) -> FontResult<Surface<'b>>
+    where
+        T: Into<Color>,
+    {
+        if self.text.is_empty() {
+            return empty_text_surface(self.font);
+        }
+        let chars: Vec<char> = self.text.decoded()?.chars().collect();
+        let color = color.into();
+        let font = self.font;
+        let glyphs = chars
+            .iter()
+            .map(|&ch| font.glyph_blit_info(font.substitute_for(ch), color))
+            .collect::<FontResult<Vec<_>>>()?;
+
+        let width = glyphs
+            .iter()
+            .map(|glyph| glyph.advance.max(glyph.surface.width() as i32))
+            .max()
+            .unwrap_or(1)
+            .max(1);
+        let line_skip = font.recommended_line_spacing().max(font.height());
+        let height = line_skip * glyphs.len() as i32;
+        let mut surface = Surface::new(width as u32, height as u32, PixelFormatEnum::ARGB8888)
+            .map_err(FontError::SdlError)?;
+        for (row, glyph) in glyphs.iter().enumerate() {
+            let x = (width - glyph.advance) / 2 + glyph.x_offset;
+            let y = row as i32 * line_skip + glyph.y_offset;
+            draw_over(&mut surface, &glyph.surface, x, y);
+        }
+        Ok(font.apply_output_alpha(surface))
     }
 
     /// Renders the text in *blended* mode but wrapping the words if the width
@@ -204,7 +932,10 @@ impl<'f, 'text> PartialRendering<'f, 'text> {
     where
         T: Into<Color>,
     {
-        let source = self.text.convert()?;
+        if self.text.is_empty() {
+            return empty_text_surface(self.font);
+        }
+        let source = self.font.c_text(&self.text)?;
         let color = color.into().into();
         let raw = unsafe {
             match self.text {
@@ -224,7 +955,7 @@ impl<'f, 'text> PartialRendering<'f, 'text> {
                 ),
             }
         };
-        convert_to_surface(raw)
+        convert_to_surface(raw).map(|surface| self.font.apply_output_alpha(surface))
     }
 }
 
@@ -239,16 +970,60 @@ pub struct Font<'ttf_module, 'rwops> {
     // side
     #[allow(dead_code)]
     rwops: Option<RWops<'rwops>>,
+    // In-memory font data shared between fonts opened from the same file at
+    // different sizes. Declared after the RWops so it outlives it.
+    #[allow(dead_code)]
+    buffer: Option<Rc<[u8]>>,
+    // The file, point size and face index the font was opened with,
+    // used to compare fonts by source. Fonts opened from an RWops
+    // have no path.
+    path: Option<PathBuf>,
+    point_size: u16,
+    index: u32,
+    // Unique to each font loaded, identifying fonts without a path.
+    id: u64,
+    // The color used by the `*_default` renderings.
+    default_color: Color,
+    // The quality of the renderings of `render_str`.
+    render_quality: RenderQuality,
+    // The point size from which `auto_render_str` renders in blended mode.
+    solid_below: u16,
+    // The character rendered in place of the characters the face lacks.
+    substitute: Option<char>,
+    // The UTF-8 texts rendered or measured lately, converted for SDL2_TTF.
+    c_strings: RefCell<HashMap<String, Rc<CString>>>,
+    // The normalization applied to UTF-8 text before it is measured or
+    // rendered.
+    #[cfg(feature = "unicode-normalization")]
+    normalization: NormalizationForm,
+    // Whether blended renderings are premultiplied.
+    output_alpha: AlphaMode,
     #[allow(dead_code)]
     _marker: PhantomData<&'ttf_module ()>,
 }
 
+/// Fonts compare by identity: two `Font`s are equal only if they wrap the
+/// same underlying C font object. See `Font::same_source` to compare fonts by
+/// the file, size and face they were opened with.
+impl<'ttf, 'r> PartialEq for Font<'ttf, 'r> {
+    fn eq(&self, other: &Font) -> bool {
+        self.raw == other.raw
+    }
+}
+
+impl<'ttf, 'r> Eq for Font<'ttf, 'r> {}
+
+/// Fonts are closed when dropped. A font dropped after `SDL2_TTF` has been
+/// quit can't be closed anymore and is leaked: debug builds panic instead, to
+/// surface the ordering bug, while release builds leak it silently.
 impl<'ttf, 'r> Drop for Font<'ttf, 'r> {
     fn drop(&mut self) {
         unsafe {
             // avoid close font after quit()
             if ttf::TTF_WasInit() == 1 {
                 ttf::TTF_CloseFont(self.raw);
+            } else if cfg!(debug_assertions) && !thread::panicking() {
+                panic!("a Font was dropped after SDL2_TTF was quit, and leaked");
             }
         }
     }
@@ -260,7 +1035,7 @@ pub fn internal_load_font<'ttf, P: AsRef<Path>>(
     ptsize: u16,
 ) -> Result<Font<'ttf, 'static>, String> {
     unsafe {
-        let cstring = CString::new(path.as_ref().to_str().unwrap()).unwrap();
+        let cstring = path_to_cstring(path.as_ref())?;
         let raw = ttf::TTF_OpenFont(cstring.as_ptr(), ptsize as c_int);
         if raw.is_null() {
             Err(get_error())
@@ -268,6 +1043,19 @@ pub fn internal_load_font<'ttf, P: AsRef<Path>>(
             Ok(Font {
                 raw: raw,
                 rwops: None,
+                buffer: None,
+                path: Some(path.as_ref().to_path_buf()),
+                point_size: ptsize,
+                index: 0,
+                id: next_font_id(),
+                default_color: Color::WHITE,
+                render_quality: RenderQuality::High,
+                solid_below: DEFAULT_SOLID_BELOW,
+                substitute: None,
+                c_strings: RefCell::new(HashMap::new()),
+                #[cfg(feature = "unicode-normalization")]
+                normalization: NormalizationForm::None,
+                output_alpha: AlphaMode::Straight,
                 _marker: PhantomData,
             })
         }
@@ -275,17 +1063,57 @@ pub fn internal_load_font<'ttf, P: AsRef<Path>>(
 }
 
 /// Internally used to load a font (for internal visibility).
-pub fn internal_load_font_from_ll<'ttf, 'r, R>(raw: *mut ttf::TTF_Font, rwops: R) -> Font<'ttf, 'r>
+pub fn internal_load_font_from_ll<'ttf, 'r, R>(
+    raw: *mut ttf::TTF_Font,
+    rwops: R,
+    index: u32,
+    ptsize: u16,
+) -> Font<'ttf, 'r>
 where
     R: Into<Option<RWops<'r>>>,
 {
     Font {
         raw: raw,
         rwops: rwops.into(),
+        buffer: None,
+        path: None,
+        point_size: ptsize,
+        index,
+        id: next_font_id(),
+        default_color: Color::WHITE,
+        render_quality: RenderQuality::High,
+        solid_below: DEFAULT_SOLID_BELOW,
+        substitute: None,
+        c_strings: RefCell::new(HashMap::new()),
+        #[cfg(feature = "unicode-normalization")]
+        normalization: NormalizationForm::None,
+        output_alpha: AlphaMode::Straight,
         _marker: PhantomData,
     }
 }
 
+/// Internally used to load a font from in-memory font data that may be shared
+/// with other fonts (for internal visibility).
+pub fn internal_load_font_from_buffer<'ttf>(
+    path: Option<&Path>,
+    buffer: Rc<[u8]>,
+    index: u32,
+    ptsize: u16,
+) -> Result<Font<'ttf, 'static>, String> {
+    // the font keeps the buffer alive for as long as the RWops exists
+    let bytes: &'static [u8] = unsafe { slice::from_raw_parts(buffer.as_ptr(), buffer.len()) };
+    let rwops = RWops::from_bytes(bytes)?;
+    let raw = unsafe { ttf::TTF_OpenFontIndexRW(rwops.raw(), 0, ptsize as c_int, index as c_long) };
+    if raw.is_null() {
+        Err(get_error())
+    } else {
+        let mut font = internal_load_font_from_ll(raw, Some(rwops), index, ptsize);
+        font.buffer = Some(buffer);
+        font.path = path.map(Path::to_path_buf);
+        Ok(font)
+    }
+}
+
 /// Internally used to load a font (for internal visibility).
 pub fn internal_load_font_at_index<'ttf, P: AsRef<Path>>(
     path: P,
@@ -293,30 +1121,348 @@ pub fn internal_load_font_at_index<'ttf, P: AsRef<Path>>(
     ptsize: u16,
 ) -> Result<Font<'ttf, 'static>, String> {
     unsafe {
-        let cstring = CString::new(path.as_ref().to_str().unwrap().as_bytes()).unwrap();
+        let cstring = path_to_cstring(path.as_ref())?;
+        // SDL2_TTF fails with a vague error for faces the file doesn't have,
+        // and the first face is the only one that tells how many there are
+        if index > 0 {
+            let first = ttf::TTF_OpenFont(cstring.as_ptr(), ptsize as c_int);
+            if first.is_null() {
+                return Err(get_error());
+            }
+            let face_count = ttf::TTF_FontFaces(first);
+            ttf::TTF_CloseFont(first);
+            check_face_index(index, face_count)?;
+        }
         let raw = ttf::TTF_OpenFontIndex(cstring.as_ptr(), ptsize as c_int, index as c_long);
         if raw.is_null() {
             Err(get_error())
+        } else if ttf::TTF_FontFaces(raw) <= 0 {
+            // a malformed file without faces
+            ttf::TTF_CloseFont(raw);
+            Err(format!(
+                "face index out of range: {}, the font has no faces",
+                index
+            ))
         } else {
             Ok(Font {
                 raw: raw,
                 rwops: None,
+                buffer: None,
+                path: Some(path.as_ref().to_path_buf()),
+                point_size: ptsize,
+                index,
+                id: next_font_id(),
+                default_color: Color::WHITE,
+                render_quality: RenderQuality::High,
+                solid_below: DEFAULT_SOLID_BELOW,
+                substitute: None,
+                c_strings: RefCell::new(HashMap::new()),
+                #[cfg(feature = "unicode-normalization")]
+                normalization: NormalizationForm::None,
+                output_alpha: AlphaMode::Straight,
                 _marker: PhantomData,
             })
         }
     }
 }
 
+fn check_face_index(index: u32, face_count: c_long) -> Result<(), String> {
+    if face_count > 0 && u64::from(index) < face_count as u64 {
+        Ok(())
+    } else {
+        Err(format!(
+            "face index out of range: {}, the font has {} faces",
+            index, face_count
+        ))
+    }
+}
+
 impl<'ttf, 'r> Font<'ttf, 'r> {
     /// Returns the underlying C font object.
     // this can prevent introducing UB until
     // https://github.com/rust-lang/rust-clippy/issues/5953 is fixed
     #[allow(clippy::trivially_copy_pass_by_ref)]
-    unsafe fn raw(&self) -> *mut ttf::TTF_Font {
+    pub(crate) unsafe fn raw(&self) -> *mut ttf::TTF_Font {
         self.raw
     }
 
+    /// Returns the point size the font was opened with.
+    pub fn point_size(&self) -> u16 {
+        self.point_size
+    }
+
+    /// Returns the index of the face the font was opened with.
+    pub fn face_index(&self) -> u32 {
+        self.index
+    }
+
+    /// Returns what the font was opened from, see `FontSource`.
+    pub(super) fn source(&self) -> FontSource {
+        match self.path {
+            Some(ref path) => FontSource::File {
+                path: path.clone(),
+                point_size: self.point_size,
+                index: self.index,
+            },
+            None => FontSource::Loaded(self.id),
+        }
+    }
+
+    /// Returns whether both fonts were opened from the same file, with the
+    /// same point size and face index.
+    ///
+    /// Fonts loaded from an `RWops` have no known source, so they only
+    /// compare equal to themselves.
+    pub fn same_source(&self, other: &Font) -> bool {
+        if self == other {
+            return true;
+        }
+        match (self.path.as_ref(), other.path.as_ref()) {
+            (Some(path), Some(other_path)) => {
+                path == other_path
+                    && self.point_size == other.point_size
+                    && self.index == other.index
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the color used by the `*_default` renderings, white unless
+    /// changed with `set_default_color`.
+    pub fn default_color(&self) -> Color {
+        self.default_color
+    }
+
+    /// Sets the color used by the `*_default` renderings, for applications
+    /// drawing most of their text in a single color.
+    pub fn set_default_color<T: Into<Color>>(&mut self, color: T) {
+        self.default_color = color.into();
+    }
+
+    /// Returns the quality of the renderings of `render_str`, `High` unless
+    /// changed with `set_render_quality`.
+    pub fn render_quality(&self) -> RenderQuality {
+        self.render_quality
+    }
+
+    /// Sets the quality of the renderings of `render_str`.
+    pub fn set_render_quality(&mut self, quality: RenderQuality) {
+        self.render_quality = quality;
+    }
+
+    /// Returns whether *blended* renderings are premultiplied, `Straight`
+    /// unless changed with `set_output_alpha`.
+    pub fn output_alpha(&self) -> AlphaMode {
+        self.output_alpha
+    }
+
+    /// Sets whether the *blended* renderings of this font have their color
+    /// channels premultiplied by alpha, so that a whole pipeline expecting
+    /// premultiplied textures doesn't need `blended_premultiplied` at every
+    /// call site.
+    ///
+    /// The helpers built on *blended* rendering, like `blended_wrapped`,
+    /// `blended_centered` or `render_colored_runs`, compose their result
+    /// with straight alpha and premultiply it last. Glyphs from
+    /// `glyph_blit_info`, `blended_at` and `TextLog` stay straight, as they
+    /// are meant to be blended further.
+    pub fn set_output_alpha(&mut self, mode: AlphaMode) {
+        self.output_alpha = mode;
+    }
+
+    /// Premultiplies a *blended* rendering if the output alpha of the font
+    /// asks for it.
+    fn apply_output_alpha<'b>(&self, mut surface: Surface<'b>) -> Surface<'b> {
+        if self.output_alpha == AlphaMode::Premultiplied {
+            map_argb_pixels(&mut surface, |_, _, pixel| premultiply_pixel(pixel));
+        }
+        surface
+    }
+
+    /// Renders the given UTF-8-encoded text in the mode matching the render
+    /// quality of the font.
+    pub fn render_str<'b, T>(&self, text: &str, color: T) -> FontResult<Surface<'b>>
+    where
+        T: Into<Color>,
+    {
+        self.render(text)
+            .with_mode(color, self.render_quality.mode())
+    }
+
+    /// Returns the point size from which `auto_render_str` renders in
+    /// *blended* mode, 12 by default.
+    pub fn auto_solid_below(&self) -> u16 {
+        self.solid_below
+    }
+
+    /// Sets the point size from which `auto_render_str` renders in *blended*
+    /// mode: smaller fonts are rendered in *solid* mode. 0 always renders in
+    /// *blended* mode.
+    pub fn set_auto_solid_below(&mut self, point_size: u16) {
+        self.solid_below = point_size;
+    }
+
+    /// Renders the given UTF-8-encoded text in *solid* mode if the point size
+    /// of the font is below `auto_solid_below`, where the hinted, aliased
+    /// glyphs are often sharper, and in *blended* mode otherwise.
+    pub fn auto_render_str<'b, T>(&self, text: &str, color: T) -> FontResult<Surface<'b>>
+    where
+        T: Into<Color>,
+    {
+        let mode = if self.point_size < self.solid_below {
+            RenderMode::Solid
+        } else {
+            RenderMode::Blended
+        };
+        self.render(text).with_mode(color, mode)
+    }
+
+    /// Returns the character rendered in place of the characters this face
+    /// doesn't provide, if any.
+    pub fn substitute(&self) -> Option<char> {
+        self.substitute
+    }
+
+    /// Renders and measures the characters this face doesn't provide as the
+    /// given character, like `'?'` or `'\u{25af}'`, rather than as the
+    /// *.notdef* glyph of the face, which is often an empty box.
+    ///
+    /// The substitute is only used if the face provides it and it isn't a
+    /// control character, like a nul, which is ignored. Control characters
+    /// of the text are kept. There is no substitute by default.
+    pub fn set_substitute(&mut self, ch: char) {
+        self.substitute = Some(ch);
+        self.clear_c_string_cache();
+    }
+
+    /// Renders the characters this face doesn't provide as the face does
+    /// again.
+    pub fn unset_substitute(&mut self) {
+        self.substitute = None;
+        self.clear_c_string_cache();
+    }
+
+    /// Returns the character to render in place of the given one.
+    fn substitute_for(&self, ch: char) -> char {
+        match self.substitute {
+            Some(substitute)
+                if !ch.is_control()
+                    && !substitute.is_control()
+                    && self.find_glyph(ch).is_none()
+                    && self.find_glyph(substitute).is_some() =>
+            {
+                substitute
+            }
+            _ => ch,
+        }
+    }
+
+    /// Returns the Unicode normalization applied to UTF-8 text, none by
+    /// default.
+    #[cfg(feature = "unicode-normalization")]
+    pub fn normalization(&self) -> NormalizationForm {
+        self.normalization
+    }
+
+    /// Sets the Unicode normalization applied to UTF-8 text before it is
+    /// measured or rendered, so that text composed differently, like NFC
+    /// text and NFD text from another source, renders and measures the same.
+    ///
+    /// Composed characters also render better, as `SDL2_TTF` draws combining
+    /// marks as glyphs of their own. The helpers laying characters out one
+    /// at a time, like `caret_positions`, `blended_tracked` and
+    /// `blended_vertical`, work on the text as given.
+    #[cfg(feature = "unicode-normalization")]
+    pub fn set_normalization(&mut self, form: NormalizationForm) {
+        self.normalization = form;
+        self.clear_c_string_cache();
+    }
+
+    /// Applies the normalization of the font to the given UTF-8 text.
+    #[cfg(feature = "unicode-normalization")]
+    fn normalized(&self, source: CString) -> CString {
+        let text = source.to_str().unwrap();
+        match self.normalization {
+            NormalizationForm::None => source,
+            NormalizationForm::Nfc if is_nfc(text) => source,
+            // normalizing can't introduce nul characters
+            NormalizationForm::Nfc => CString::new(text.nfc().collect::<String>()).unwrap(),
+        }
+    }
+
+    #[cfg(not(feature = "unicode-normalization"))]
+    fn normalized(&self, source: CString) -> CString {
+        source
+    }
+
+    /// Converts the given text to a c-style string like
+    /// `RenderableText::convert`, normalized if it is UTF-8 text and with
+    /// the characters this face doesn't provide replaced by the substitute.
+    fn substituted(&self, text: &RenderableText) -> FontResult<CString> {
+        let source = match *text {
+            RenderableText::Latin1(_) => text.convert()?,
+            RenderableText::Utf8(_) | RenderableText::Char(_) => self.normalized(text.convert()?),
+        };
+        let substitute = match self.substitute {
+            Some(ch) if !ch.is_control() && self.find_glyph(ch).is_some() => ch,
+            _ => return Ok(source),
+        };
+        // neither text has nul characters anymore, nor gains any
+        match *text {
+            RenderableText::Latin1(bytes) => {
+                if substitute as u32 > 0xff {
+                    return Ok(source);
+                }
+                let bytes: Vec<u8> = bytes
+                    .iter()
+                    .map(|&byte| self.substitute_for(byte as char) as u32 as u8)
+                    .collect();
+                Ok(CString::new(bytes).unwrap())
+            }
+            RenderableText::Utf8(_) | RenderableText::Char(_) => {
+                let text = source.to_str().unwrap();
+                let chars = text.chars().map(|ch| self.substitute_for(ch));
+                if chars.clone().eq(text.chars()) {
+                    return Ok(source);
+                }
+                Ok(CString::new(chars.collect::<String>()).unwrap())
+            }
+        }
+    }
+
+    /// Drops the UTF-8 texts kept converted for `SDL2_TTF`.
+    ///
+    /// Rendering or measuring UTF-8 text needs a nul-terminated copy of it,
+    /// so the font keeps the copies of the last texts it was given, up to 64,
+    /// and reuses them when the same texts are rendered again, like the
+    /// labels of a HUD every frame. They are all dropped once there are 64.
+    pub fn clear_c_string_cache(&self) {
+        self.c_strings.borrow_mut().clear();
+    }
+
+    /// Converts the given text like `substituted`, reusing the conversion
+    /// of UTF-8 texts converted lately.
+    fn c_text(&self, text: &RenderableText) -> FontResult<Rc<CString>> {
+        let key = match *text {
+            RenderableText::Utf8(key) => key,
+            _ => return self.substituted(text).map(Rc::new),
+        };
+        if let Some(c_string) = self.c_strings.borrow().get(key) {
+            return Ok(c_string.clone());
+        }
+        let c_string = Rc::new(self.substituted(text)?);
+        let mut c_strings = self.c_strings.borrow_mut();
+        if c_strings.len() >= C_STRING_CACHE_CAPACITY {
+            c_strings.clear();
+        }
+        c_strings.insert(key.to_owned(), c_string.clone());
+        Ok(c_string)
+    }
+
     /// Starts specifying a rendering of the given UTF-8-encoded text.
+    ///
+    /// Rendering an empty string gives a transparent surface one pixel wide
+    /// and `height()` pixels tall rather than an error.
     pub fn render<'a, 'b>(&'a self, text: &'b str) -> PartialRendering<'a, 'b> {
         PartialRendering {
             text: RenderableText::Utf8(text),
@@ -342,10 +1488,70 @@ impl<'ttf, 'r> Font<'ttf, 'r> {
         }
     }
 
+    /// Renders each of the given characters once in *blended* mode and
+    /// discards the result, so that FreeType loads their glyphs ahead of
+    /// time rather than during the frame that first draws them.
+    ///
+    /// This is a best-effort warmup: `SDL2_TTF` only caches a limited number
+    /// of glyphs, so characters can evict each other, and it drops its cache
+    /// whenever the style, outline, hinting or kerning of the font changes.
+    /// Control characters are skipped.
+    pub fn warm_cache(&self, chars: &str) -> FontResult<()> {
+        let mut chars: Vec<char> = chars
+            .chars()
+            .filter(|ch| !ch.is_control())
+            .map(|ch| self.substitute_for(ch))
+            .collect();
+        chars.sort_unstable();
+        chars.dedup();
+        for ch in chars {
+            self.render_char(ch).blended(Color::WHITE)?;
+        }
+        Ok(())
+    }
+
+    /// Renders runs of UTF-8 text in *blended* mode one after the other on
+    /// a single line, each in its own color, or in `default_color` for runs
+    /// without one. This is meant for highlighting a few words.
+    ///
+    /// Runs are rendered separately, so there is no kerning between the last
+    /// character of a run and the first character of the next one.
+    pub fn render_colored_runs<C>(
+        &self,
+        runs: &[(&str, Option<C>)],
+        default_color: C,
+    ) -> FontResult<Surface<'static>>
+    where
+        C: Into<Color> + Copy,
+    {
+        let mut surfaces = Vec::with_capacity(runs.len());
+        for &(text, color) in runs.iter().filter(|&&(text, _)| !text.is_empty()) {
+            let color = color.unwrap_or(default_color);
+            surfaces.push(self.render(text).blended_straight(color.into())?);
+        }
+        let width: u32 = surfaces.iter().map(|surface| surface.width()).sum();
+        if width == 0 {
+            return empty_text_surface(self);
+        }
+        let height = surfaces
+            .iter()
+            .map(|surface| surface.height())
+            .max()
+            .unwrap_or(1);
+        let mut line =
+            Surface::new(width, height, PixelFormatEnum::ARGB8888).map_err(FontError::SdlError)?;
+        let mut x = 0;
+        for surface in &surfaces {
+            draw_over(&mut line, surface, x, 0);
+            x += surface.width() as i32;
+        }
+        Ok(self.apply_output_alpha(line))
+    }
+
     /// Returns the width and height of the given text when rendered using this
     /// font.
     pub fn size_of(&self, text: &str) -> FontResult<(u32, u32)> {
-        let c_string = RenderableText::Utf8(text).convert()?;
+        let c_string = self.c_text(&RenderableText::Utf8(text))?;
         let (res, size) = unsafe {
             let mut w = 0; // mutated by C code
             let mut h = 0; // mutated by C code
@@ -359,11 +1565,40 @@ impl<'ttf, 'r> Font<'ttf, 'r> {
         }
     }
 
+    /// Returns the width and height of the given text when rendered with
+    /// `PartialRendering::blended_tracked` and the same tracking.
+    pub fn size_of_tracked(&self, text: &str, extra_px: i32) -> FontResult<(u32, u32)> {
+        RenderableText::Utf8(text).convert()?;
+        let chars: Vec<char> = text.chars().collect();
+        let (_, width) = self.tracked_layout(&chars, extra_px)?;
+        Ok((width as u32, self.height().max(0) as u32))
+    }
+
+    /// Returns the pen position of each character with the given tracking,
+    /// and the width of the text, which covers both the advances and the
+    /// glyphs.
+    fn tracked_layout(&self, chars: &[char], extra_px: i32) -> FontResult<(Vec<i32>, i32)> {
+        let mut pens = Vec::with_capacity(chars.len());
+        let mut pen = 0;
+        let mut width = 0;
+        for (i, &ch) in chars.iter().enumerate() {
+            let metrics = self
+                .find_glyph_metrics(self.substitute_for(ch))
+                .ok_or_else(|| FontError::SdlError(get_error()))?;
+            pens.push(pen);
+            width = width.max(pen + metrics.advance).max(pen + metrics.maxx);
+            if i + 1 < chars.len() {
+                pen += (metrics.advance + extra_px).max((metrics.advance + 1) / 2);
+            }
+        }
+        Ok((pens, width.max(0)))
+    }
+
     /// Returns the width and height of the given text when rendered using this
     /// font.
     #[allow(unused_mut)]
     pub fn size_of_latin1(&self, text: &[u8]) -> FontResult<(u32, u32)> {
-        let c_string = RenderableText::Latin1(text).convert()?;
+        let c_string = self.c_text(&RenderableText::Latin1(text))?;
         let (res, size) = unsafe {
             let mut w: i32 = 0; // mutated by C code
             let mut h: i32 = 0; // mutated by C code
@@ -390,6 +1625,109 @@ impl<'ttf, 'r> Font<'ttf, 'r> {
         self.size_of(&s)
     }
 
+    /// Returns the width of a single grapheme cluster, such as a letter
+    /// followed by combining diacritics, for moving a cursor through text.
+    ///
+    /// `SDL2_TTF` lays out each character separately without shaping, so a
+    /// decomposed cluster like `"e\u{301}"` measures the same as its
+    /// precomposed form `"\u{e9}"` as long as the font gives its combining
+    /// marks no advance, which most fonts do.
+    pub fn grapheme_width(&self, grapheme: &str) -> FontResult<u32> {
+        if grapheme.is_empty() {
+            return Ok(0);
+        }
+        self.size_of(grapheme).map(|(width, _)| width)
+    }
+
+    /// Returns the horizontal position of the caret before each character of
+    /// the given text, followed by its position at the end of the text, for
+    /// text input widgets.
+    ///
+    /// Positions are measured on the text up to each character, so they
+    /// account for kerning, but this takes time quadratic in the length of
+    /// the text.
+    ///
+    /// `SDL2_TTF` has no text direction: it lays characters out from left to
+    /// right in the order they are stored, including right-to-left scripts,
+    /// so the positions always increase. See `caret_positions_rtl` for
+    /// right-to-left text reversed before rendering.
+    pub fn caret_positions(&self, text: &str) -> FontResult<Vec<i32>> {
+        let mut positions = vec![0];
+        for (index, ch) in text.char_indices() {
+            let (width, _) = self.size_of(&text[..index + ch.len_utf8()])?;
+            positions.push(width as i32);
+        }
+        Ok(positions)
+    }
+
+    /// Returns the index, in characters, of the caret position of the given
+    /// text that is nearest to `x`, for placing the caret where the user
+    /// clicked. See `caret_positions`.
+    pub fn caret_at(&self, text: &str, x: i32) -> FontResult<usize> {
+        self.caret_positions(text)
+            .map(|positions| nearest_caret(&positions, x))
+    }
+
+    /// Returns the horizontal position of the caret before each character of
+    /// the given right-to-left text, like Hebrew or Arabic, followed by its
+    /// position at the end of the text, when the text is rendered reversed.
+    ///
+    /// Since `SDL2_TTF` lays characters out from left to right, right-to-left
+    /// text reads correctly once its characters are reversed, for example
+    /// with `text.chars().rev().collect::<String>()`, which is what is
+    /// measured. The positions are still given in the order of `text`, so
+    /// they decrease from the width of the text down to 0. Characters aren't
+    /// shaped, so this suits Hebrew better than Arabic.
+    pub fn caret_positions_rtl(&self, text: &str) -> FontResult<Vec<i32>> {
+        let reversed: String = text.chars().rev().collect();
+        let mut positions = self.caret_positions(&reversed)?;
+        positions.reverse();
+        Ok(positions)
+    }
+
+    /// Returns the index, in characters, of the caret position of the given
+    /// right-to-left text that is nearest to `x`. See `caret_positions_rtl`.
+    pub fn caret_at_rtl(&self, text: &str, x: i32) -> FontResult<usize> {
+        self.caret_positions_rtl(text)
+            .map(|positions| nearest_caret(&positions, x))
+    }
+
+    /// Returns the width in pixels of the widest line of the given text,
+    /// whose lines are separated by `\n`, for sizing a box around it.
+    pub fn max_line_width(&self, text: &str) -> FontResult<u32> {
+        text.split('\n')
+            .map(|line| line.strip_suffix('\r').unwrap_or(line))
+            .filter(|line| !line.is_empty())
+            .try_fold(0, |widest, line| {
+                self.size_of(line).map(|(width, _)| widest.max(width))
+            })
+    }
+
+    /// Returns the number of lines of the given text, separated by `\n`, as
+    /// measured by `max_line_width`. Empty text is a single empty line.
+    pub fn line_count(&self, text: &str) -> usize {
+        text.split('\n').count()
+    }
+
+    /// Returns the difference in pixels between the rendered width of the
+    /// given text and the sum of the advances of its characters.
+    ///
+    /// A nonzero value means kerning affects the layout of the text. Note that
+    /// styles and outlines that widen the rendered text are also reflected in
+    /// the result.
+    pub fn kerning_adjustment(&self, text: &str) -> FontResult<i32> {
+        let (width, _) = self.size_of(text)?;
+        // missing characters render as the .notdef glyph, which advances too
+        let advances: i32 = text
+            .chars()
+            .map(|ch| {
+                self.find_glyph_metrics(ch)
+                    .map_or(0, |metrics| metrics.advance)
+            })
+            .sum();
+        Ok(width as i32 - advances)
+    }
+
     /// Returns the font's style flags.
     pub fn get_style(&self) -> FontStyle {
         unsafe {
@@ -440,6 +1778,33 @@ impl<'ttf, 'r> Font<'ttf, 'r> {
         unsafe { ttf::TTF_SetFontKerning(self.raw, kerning as c_int) }
     }
 
+    /// Returns the position of the underline below the baseline and its
+    /// thickness in pixels, as designed for the font face, to draw matching
+    /// underlines with `PartialRendering::blended_underlined`.
+    ///
+    /// The `SDL2_ttf` 2.0 API these bindings are built against does not
+    /// expose these metrics of the FreeType face, so this always returns
+    /// `None`.
+    pub fn underline_metrics(&self) -> Option<(i32, i32)> {
+        None
+    }
+
+    /// Enables LCD subpixel antialiasing, where each color channel of a
+    /// pixel is rendered separately, instead of grayscale antialiasing.
+    ///
+    /// The `SDL2_ttf` 2.0 API these bindings are built against has no LCD
+    /// rendering mode, so enabling it always fails. Disabling it succeeds,
+    /// as grayscale antialiasing is what is always used.
+    pub fn set_lcd(&mut self, enabled: bool) -> FontResult<()> {
+        if enabled {
+            Err(FontError::SdlError(
+                "LCD subpixel rendering is not supported by this version of SDL2_ttf".to_owned(),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
     pub fn height(&self) -> i32 {
         //! Get font maximum total height.
         unsafe { ttf::TTF_FontHeight(self.raw) as i32 }
@@ -461,6 +1826,16 @@ impl<'ttf, 'r> Font<'ttf, 'r> {
         unsafe { ttf::TTF_FontLineSkip(self.raw) as i32 }
     }
 
+    /// Returns the line spacing to use for text rendered with the outline of
+    /// this font, so that outlined lines don't overlap.
+    ///
+    /// The outline grows each glyph by its width on every side, which makes
+    /// rendered lines `2 * get_outline_width()` pixels taller, but the
+    /// metrics of `SDL2_TTF`, `recommended_line_spacing` included, ignore it.
+    pub fn effective_line_skip(&self) -> i32 {
+        self.recommended_line_spacing() + 2 * i32::from(self.get_outline_width())
+    }
+
     /// Returns the number of faces in this font.
     pub fn face_count(&self) -> u16 {
         unsafe { ttf::TTF_FontFaces(self.raw) as u16 }
@@ -471,6 +1846,36 @@ impl<'ttf, 'r> Font<'ttf, 'r> {
         unsafe { ttf::TTF_FontFaceIsFixedWidth(self.raw) != 0 }
     }
 
+    /// Returns whether the font face is made of outlines that scale to any
+    /// point size, rather than of fixed-size bitmap strikes.
+    ///
+    /// `SDL2_ttf` does not expose the FreeType face flags, so this opens the
+    /// face again at 100 and 200 points and checks that the height grows
+    /// with the size: a bitmap font gets the closest strike it has at both
+    /// sizes. Fonts opened from an `RWops` can't be opened again, so they
+    /// are assumed to be scalable.
+    pub fn is_scalable(&self) -> bool {
+        match (self.open_again(100), self.open_again(200)) {
+            (Ok(small), Ok(large)) => large.height() * 2 >= small.height() * 3,
+            _ => true,
+        }
+    }
+
+    /// Opens the face of this font again at another size, if its source is
+    /// known.
+    fn open_again(&self, ptsize: u16) -> Result<Font<'ttf, 'static>, String> {
+        match (self.buffer.as_ref(), self.path.as_ref()) {
+            (Some(buffer), path) => internal_load_font_from_buffer(
+                path.map(PathBuf::as_path),
+                buffer.clone(),
+                self.index,
+                ptsize,
+            ),
+            (None, Some(path)) => internal_load_font_at_index(path, self.index, ptsize),
+            (None, None) => Err("A font opened from an RWops can't be opened again".to_owned()),
+        }
+    }
+
     /// Returns the family name of the current font face.
     pub fn face_family_name(&self) -> Option<String> {
         unsafe {
@@ -498,32 +1903,81 @@ impl<'ttf, 'r> Font<'ttf, 'r> {
 
     /// Returns the index of the given character in this font face.
     pub fn find_glyph(&self, ch: char) -> Option<u16> {
-        unsafe {
-            let ret = ttf::TTF_GlyphIsProvided(self.raw, ch as u16);
-            if ret == 0 {
-                None
-            } else {
-                Some(ret as u16)
+        match self.glyph_index(ch) {
+            Some(0) | None => None,
+            Some(index) => Some(index as u16),
+        }
+    }
+
+    /// Returns the raw glyph index the font face maps the given character
+    /// to.
+    ///
+    /// Index 0 is the *.notdef* glyph, which fonts draw for characters they
+    /// don't provide, usually as an empty box: `Some(0)` means the character
+    /// is missing from this face, and would render as that box. `None` means
+    /// the character can't be looked up at all, since `SDL2_ttf` only looks
+    /// up characters of the Basic Multilingual Plane.
+    pub fn glyph_index(&self, ch: char) -> Option<u32> {
+        if ch as u32 > 0xffff {
+            return None;
+        }
+        let ret = unsafe { ttf::TTF_GlyphIsProvided(self.raw, ch as u16) };
+        Some(ret as u32)
+    }
+
+    /// Returns the ranges of characters provided by this font face, as
+    /// sorted and inclusive `(first, last)` pairs.
+    ///
+    /// The scan stops at `last`, or at the end of the Basic Multilingual
+    /// Plane, which is all that `SDL2_ttf` can look up glyphs for.
+    pub fn coverage_ranges(&self, last: Option<char>) -> Vec<(char, char)> {
+        let last = last.map_or(0xffff, |ch| (ch as u32).min(0xffff));
+        let mut ranges: Vec<(char, char)> = Vec::new();
+        let mut in_range = false;
+        // surrogates aren't characters, and end any range
+        for ch in (0..=last).map(char::from_u32) {
+            match ch {
+                Some(ch) if self.find_glyph(ch).is_some() => {
+                    if in_range {
+                        ranges.last_mut().unwrap().1 = ch;
+                    } else {
+                        ranges.push((ch, ch));
+                        in_range = true;
+                    }
+                }
+                _ => in_range = false,
             }
         }
+        ranges
+    }
+
+    /// Gathers the names, faces, metrics and character coverage of this font
+    /// into a report for diagnostics, which prints as a few lines of text.
+    ///
+    /// This scans the whole Basic Multilingual Plane and opens the font again
+    /// for `is_scalable`, so it is meant for tooling rather than every frame.
+    pub fn report(&self) -> FontReport {
+        FontReport::new(self)
     }
 
     /// Returns the glyph metrics of the given character in this font face.
     pub fn find_glyph_metrics(&self, ch: char) -> Option<GlyphMetrics> {
-        let minx = 0;
-        let maxx = 0;
-        let miny = 0;
-        let maxy = 0;
-        let advance = 0;
+        // mutated by C code, which writing through shared references would
+        // let the compiler ignore
+        let mut minx = 0;
+        let mut maxx = 0;
+        let mut miny = 0;
+        let mut maxy = 0;
+        let mut advance = 0;
         let ret = unsafe {
             ttf::TTF_GlyphMetrics(
                 self.raw,
                 ch as u16,
-                &minx as *const _ as *mut _,
-                &maxx as *const _ as *mut _,
-                &miny as *const _ as *mut _,
-                &maxy as *const _ as *mut _,
-                &advance as *const _ as *mut _,
+                &mut minx,
+                &mut maxx,
+                &mut miny,
+                &mut maxy,
+                &mut advance,
             )
         };
         if ret == 0 {
@@ -538,4 +1992,85 @@ impl<'ttf, 'r> Font<'ttf, 'r> {
             None
         }
     }
+
+    /// Renders a single glyph in *blended* mode, cropped to its bounding
+    /// box, along with the offsets to blit it at from the pen position.
+    ///
+    /// This is meant for custom text layout: blit each glyph at
+    /// `(pen_x + x_offset, line_top + y_offset)`, then move the pen by
+    /// `advance`. Kerning is not applied. The glyph has straight alpha,
+    /// whatever the output alpha of the font.
+    pub fn glyph_blit_info<T>(&self, ch: char, color: T) -> FontResult<GlyphBlit>
+    where
+        T: Into<Color>,
+    {
+        let metrics = self
+            .find_glyph_metrics(ch)
+            .ok_or_else(|| FontError::SdlError(get_error()))?;
+        let rendered = self.render_char(ch).blended_straight(color.into())?;
+
+        // SDL2_TTF shifts the pen right when the glyph extends to its left,
+        // and draws the glyph ascent - maxy pixels below the top of the line
+        let y_offset = self.ascent() - metrics.maxy;
+        let x = metrics.minx.max(0);
+        let y = y_offset.max(0);
+        let width = (metrics.maxx - metrics.minx).min(rendered.width() as i32 - x);
+        let height = (metrics.maxy - metrics.miny).min(rendered.height() as i32 - y);
+        // glyphs without any pixels, like spaces, get a single transparent one
+        let rect = Rect::new(x, y, width.max(1) as u32, height.max(1) as u32);
+        Ok(GlyphBlit {
+            surface: crop_surface(&rendered, rect)?,
+            x_offset: metrics.minx,
+            y_offset,
+            advance: metrics.advance,
+        })
+    }
+
+    /// Returns the coverage of a single glyph rendered in *blended* mode: its
+    /// alpha values from 0 to 255, row by row without padding, along with the
+    /// width and height of the grid.
+    ///
+    /// The grid is cropped to the bounding box of the glyph, like the surface
+    /// of `glyph_blit_info`. This is meant for baking signed distance fields
+    /// or uploading glyphs to custom renderers.
+    pub fn glyph_coverage(&self, ch: char) -> FontResult<(Vec<u8>, u32, u32)> {
+        let glyph = self.glyph_blit_info(ch, Color::WHITE)?.surface;
+        let (width, height) = glyph.size();
+        let pitch = glyph.pitch() as usize;
+        let mut coverage = Vec::with_capacity(width as usize * height as usize);
+        glyph.with_lock(|pixels| {
+            for y in 0..height as usize {
+                let row = &pixels[y * pitch..y * pitch + width as usize * 4];
+                coverage.extend(
+                    row.chunks(4)
+                        .map(|p| (u32::from_ne_bytes([p[0], p[1], p[2], p[3]]) >> 24) as u8),
+                );
+            }
+        });
+        Ok((coverage, width, height))
+    }
+
+    /// Returns the horizontal advance of the given character in this font
+    /// face, or `None` if the glyph is missing.
+    pub fn advance(&self, ch: char) -> Option<i32> {
+        // SDL2_TTF measures the .notdef glyph for missing characters
+        self.find_glyph(ch)?;
+        let mut advance = 0;
+        let ret = unsafe {
+            ttf::TTF_GlyphMetrics(
+                self.raw,
+                ch as u16,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                &mut advance,
+            )
+        };
+        if ret == 0 {
+            Some(advance as i32)
+        } else {
+            None
+        }
+    }
 }
@@ -0,0 +1,109 @@
+use pixels::Color;
+use std::os::raw::c_int;
+use surface::Surface;
+use sys::ttf;
+
+use super::font::{Font, FontResult, FontStyle};
+
+/// A piece of text rendered in *blended* mode with a font, keeping its
+/// rendering until its text, color or style change, for user interface
+/// labels that are drawn every frame but rarely change.
+///
+/// The style belongs to the label and is only set on the font while
+/// rendering, so labels sharing a font can have different styles. The font
+/// is borrowed, so its other settings can't change while the label exists.
+pub struct Label<'f, 'ttf: 'f, 'r: 'f> {
+    font: &'f Font<'ttf, 'r>,
+    text: String,
+    color: Color,
+    style: FontStyle,
+    rendered: Option<Surface<'static>>,
+}
+
+impl<'f, 'ttf, 'r> Label<'f, 'ttf, 'r> {
+    /// Creates a label, with the current style of the font. The text is
+    /// only rendered once the surface is first needed.
+    pub fn new<T: Into<Color>>(font: &'f Font<'ttf, 'r>, text: &str, color: T) -> Self {
+        Label {
+            font,
+            text: text.to_owned(),
+            color: color.into(),
+            style: font.get_style(),
+            rendered: None,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn color(&self) -> Color {
+        self.color
+    }
+
+    pub fn style(&self) -> FontStyle {
+        self.style
+    }
+
+    /// Changes the text, which is rendered again unless it is the same.
+    pub fn set_text(&mut self, text: &str) {
+        if self.text != text {
+            self.text.clear();
+            self.text.push_str(text);
+            self.rendered = None;
+        }
+    }
+
+    /// Changes the color, which renders the text again unless it is the
+    /// same.
+    pub fn set_color<T: Into<Color>>(&mut self, color: T) {
+        let color = color.into();
+        if self.color != color {
+            self.color = color;
+            self.rendered = None;
+        }
+    }
+
+    /// Changes the style, which renders the text again unless it is the
+    /// same.
+    pub fn set_style(&mut self, style: FontStyle) {
+        if self.style != style {
+            self.style = style;
+            self.rendered = None;
+        }
+    }
+
+    /// Returns whether the text will be rendered again when the surface is
+    /// next needed.
+    pub fn is_dirty(&self) -> bool {
+        self.rendered.is_none()
+    }
+
+    /// Returns the rendered text, rendering it first if it changed.
+    pub fn surface(&mut self) -> FontResult<&Surface<'static>> {
+        if self.rendered.is_none() {
+            self.rendered = Some(self.render()?);
+        }
+        Ok(self.rendered.as_ref().unwrap())
+    }
+
+    /// Returns the size of the rendered text, rendering it first if it
+    /// changed.
+    pub fn size(&mut self) -> FontResult<(u32, u32)> {
+        self.surface().map(|surface| surface.size())
+    }
+
+    fn render(&self) -> FontResult<Surface<'static>> {
+        let font = self.font;
+        let style = font.get_style();
+        // changing the style flushes the glyph cache of SDL2_TTF, so the
+        // style of the font is only changed when it differs
+        if style == self.style {
+            return font.render(&self.text).blended(self.color);
+        }
+        unsafe { ttf::TTF_SetFontStyle(font.raw(), self.style.bits() as c_int) };
+        let rendered = font.render(&self.text).blended(self.color);
+        unsafe { ttf::TTF_SetFontStyle(font.raw(), style.bits() as c_int) };
+        rendered
+    }
+}
@@ -0,0 +1,149 @@
+use std::fs::File;
+use std::io::Read;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use super::context::Sdl2TtfContext;
+use super::font::{internal_load_font_from_buffer, Font};
+
+type ReadResult = Result<Vec<u8>, String>;
+
+/// A font file to read, and where to send its content.
+struct Job {
+    path: PathBuf,
+    result: Sender<ReadResult>,
+}
+
+fn read_file(path: &Path) -> ReadResult {
+    let mut data = Vec::new();
+    File::open(path)
+        .and_then(|mut file| file.read_to_end(&mut data))
+        .map_err(|err| format!("IO error: {}", err))?;
+    Ok(data)
+}
+
+/// Loads fonts while reading their files on a pool of background threads.
+///
+/// Only the file reads happen off-thread. `SDL2_ttf` is not thread-safe, so
+/// each font is opened from the data read by `FontFuture::wait` or
+/// `FontFuture::try_wait`, on the thread calling them, which should be the
+/// thread that initialized `SDL2_ttf`.
+///
+/// Dropping the loader waits for the pending reads to finish.
+pub struct FontLoader<'ttf> {
+    jobs: Option<Sender<Job>>,
+    workers: Vec<JoinHandle<()>>,
+    _marker: PhantomData<&'ttf Sdl2TtfContext>,
+}
+
+impl<'ttf> FontLoader<'ttf> {
+    /// Starts a loader reading files on the given number of threads.
+    pub fn new(_context: &'ttf Sdl2TtfContext, threads: usize) -> FontLoader<'ttf> {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let workers = (0..threads.max(1))
+            .map(|_| {
+                let receiver = receiver.clone();
+                thread::spawn(move || loop {
+                    let job = match receiver.lock() {
+                        Ok(receiver) => receiver.recv(),
+                        Err(_) => return,
+                    };
+                    match job {
+                        Ok(job) => {
+                            // the future may have been dropped in the meantime
+                            let _ = job.result.send(read_file(&job.path));
+                        }
+                        Err(_) => return,
+                    }
+                })
+            })
+            .collect();
+        FontLoader {
+            jobs: Some(sender),
+            workers,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Starts reading the given font file in the background. The font is
+    /// opened with the given size in points once the returned future is
+    /// waited on.
+    pub fn load_async<P: AsRef<Path>>(&self, path: P, point_size: u16) -> FontFuture<'ttf> {
+        let (sender, receiver) = mpsc::channel();
+        let path = path.as_ref().to_path_buf();
+        let job = Job {
+            path: path.clone(),
+            result: sender,
+        };
+        if let Some(ref jobs) = self.jobs {
+            // if every worker is gone, the future reports it when waited on
+            let _ = jobs.send(job);
+        }
+        FontFuture {
+            path,
+            point_size,
+            result: receiver,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'ttf> Drop for FontLoader<'ttf> {
+    fn drop(&mut self) {
+        // closing the channel stops the workers once the queue is empty
+        self.jobs = None;
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// A font being loaded by a `FontLoader`.
+#[must_use]
+pub struct FontFuture<'ttf> {
+    path: PathBuf,
+    point_size: u16,
+    result: Receiver<ReadResult>,
+    _marker: PhantomData<&'ttf Sdl2TtfContext>,
+}
+
+impl<'ttf> FontFuture<'ttf> {
+    /// The path of the font file being loaded.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Blocks until the font file has been read, then opens the font on the
+    /// current thread.
+    pub fn wait(self) -> Result<Font<'ttf, 'static>, String> {
+        match self.result.recv() {
+            Ok(data) => self.open(data),
+            Err(_) => Err(self.stopped()),
+        }
+    }
+
+    /// Opens the font on the current thread if its file has been read, or
+    /// returns `None` if it is still being read.
+    pub fn try_wait(&self) -> Option<Result<Font<'ttf, 'static>, String>> {
+        match self.result.try_recv() {
+            Ok(data) => Some(self.open(data)),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(self.stopped())),
+        }
+    }
+
+    fn open(&self, data: ReadResult) -> Result<Font<'ttf, 'static>, String> {
+        internal_load_font_from_buffer(Some(&self.path), data?.into(), 0, self.point_size)
+    }
+
+    fn stopped(&self) -> String {
+        format!(
+            "The font loader stopped before reading {}",
+            self.path.display()
+        )
+    }
+}
@@ -0,0 +1,87 @@
+use pixels::{Color, PixelFormatEnum};
+use surface::{Surface, SurfaceRef};
+
+use super::compose::draw_over;
+use super::font::{Font, FontError, FontResult};
+
+/// Lines of text rendered one after another onto a single surface, for
+/// displays that only ever grow, like chat logs and consoles.
+///
+/// Pushing a line only renders that line, in *blended* mode, below the
+/// previous ones. The surface grows to fit the lines, doubling its height
+/// when it is full, so it is usually taller than its content: the rows
+/// below `height` are transparent.
+pub struct TextLog {
+    surface: Surface<'static>,
+    height: u32,
+    lines: usize,
+}
+
+impl TextLog {
+    /// Creates an empty log with a surface of the given initial width, which
+    /// grows if a wider line is pushed.
+    pub fn new(width: u32) -> FontResult<TextLog> {
+        let surface = Surface::new(width.max(1), 1, PixelFormatEnum::ARGB8888)
+            .map_err(FontError::SdlError)?;
+        Ok(TextLog {
+            surface,
+            height: 0,
+            lines: 0,
+        })
+    }
+
+    /// The surface holding the lines, to upload as a texture.
+    pub fn surface(&self) -> &SurfaceRef {
+        &self.surface
+    }
+
+    /// The height of the lines pushed so far, at the top of the surface.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The number of lines pushed so far.
+    pub fn line_count(&self) -> usize {
+        self.lines
+    }
+
+    /// Removes every line, keeping the surface to draw the next ones on.
+    pub fn clear(&mut self) {
+        self.surface.with_lock_mut(|pixels| {
+            for byte in pixels.iter_mut() {
+                *byte = 0;
+            }
+        });
+        self.height = 0;
+        self.lines = 0;
+    }
+
+    /// Renders the given line with the font and appends it below the
+    /// previous ones, one line of the font further down.
+    pub fn push_line(&mut self, font: &Font, text: &str, color: Color) -> FontResult<()> {
+        let line = font.render(text).blended_straight(color)?;
+        let top = self.height;
+        let bottom = top + (font.recommended_line_spacing().max(0) as u32).max(line.height());
+        self.reserve(line.width(), bottom)?;
+        draw_over(&mut self.surface, &line, 0, top as i32);
+        self.height = bottom;
+        self.lines += 1;
+        Ok(())
+    }
+
+    /// Grows the surface to at least the given size, copying its content.
+    fn reserve(&mut self, width: u32, height: u32) -> FontResult<()> {
+        let (current_width, current_height) = self.surface.size();
+        if width <= current_width && height <= current_height {
+            return Ok(());
+        }
+        let width = width.max(current_width);
+        let height = height.max(current_height * 2);
+        let mut surface =
+            Surface::new(width, height, PixelFormatEnum::ARGB8888).map_err(FontError::SdlError)?;
+        // new surfaces are transparent, so drawing over them copies
+        draw_over(&mut surface, &self.surface, 0, 0);
+        self.surface = surface;
+        Ok(())
+    }
+}
@@ -19,13 +19,35 @@
 //! default-features = false
 //! features = ["ttf"]
 //! ```
+//!
+//! The functions of this module don't panic on any response of `SDL2_ttf`
+//! or on invalid input such as text containing nul characters: failures are
+//! reported through `Result` or `Option`.
 
+mod atlas;
+mod cache;
+mod compose;
 mod context;
 mod font;
+mod label;
+mod loader;
+mod log;
+mod report;
 
+pub use self::atlas::AsciiAtlas;
+pub use self::cache::{SharedTextCache, TextCache};
+pub use self::compose::{compose_baseline, tint_surface};
 pub use self::context::{
-    get_linked_version, has_been_initialized, init, InitError, Sdl2TtfContext,
+    clear_font_search_path, font_search_path, get_linked_version, has_been_initialized, init,
+    set_font_search_path, InitError, Sdl2TtfContext,
 };
+#[cfg(feature = "unicode-normalization")]
+pub use self::font::NormalizationForm;
 pub use self::font::{
-    Font, FontError, FontResult, FontStyle, GlyphMetrics, Hinting, PartialRendering,
+    AlphaMode, FaceInfo, Font, FontError, FontResult, FontStyle, GlyphBlit, GlyphMetrics, Hinting,
+    PartialRendering, RenderMode, RenderQuality,
 };
+pub use self::label::Label;
+pub use self::loader::{FontFuture, FontLoader};
+pub use self::log::TextLog;
+pub use self::report::FontReport;
@@ -0,0 +1,91 @@
+use std::fmt;
+
+use super::font::Font;
+
+/// A summary of a font for diagnostics, gathered by `Font::report`, which
+/// displays as a few lines of text.
+#[derive(Debug, PartialEq, Clone)]
+pub struct FontReport {
+    pub family_name: Option<String>,
+    pub style_name: Option<String>,
+    pub point_size: u16,
+    pub face_index: u32,
+    pub face_count: u16,
+    pub is_fixed_width: bool,
+    pub is_scalable: bool,
+    pub height: i32,
+    pub ascent: i32,
+    pub descent: i32,
+    pub line_skip: i32,
+    /// The number of characters the face provides, within the Basic
+    /// Multilingual Plane.
+    pub char_count: u32,
+    /// The ranges of characters the face provides, see
+    /// `Font::coverage_ranges`.
+    pub coverage: Vec<(char, char)>,
+}
+
+impl FontReport {
+    pub(super) fn new(font: &Font) -> FontReport {
+        let coverage = font.coverage_ranges(None);
+        let char_count = coverage
+            .iter()
+            .map(|&(first, last)| last as u32 - first as u32 + 1)
+            .sum();
+        FontReport {
+            family_name: font.face_family_name(),
+            style_name: font.face_style_name(),
+            point_size: font.point_size(),
+            face_index: font.face_index(),
+            face_count: font.face_count(),
+            is_fixed_width: font.face_is_fixed_width(),
+            is_scalable: font.is_scalable(),
+            height: font.height(),
+            ascent: font.ascent(),
+            descent: font.descent(),
+            line_skip: font.recommended_line_spacing(),
+            char_count,
+            coverage,
+        }
+    }
+}
+
+impl fmt::Display for FontReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        let yes_no = |value: bool| if value { "yes" } else { "no" };
+        writeln!(
+            f,
+            "{} {}, {} pt (face {} of {})",
+            self.family_name.as_deref().unwrap_or("(unnamed)"),
+            self.style_name.as_deref().unwrap_or("(unnamed)"),
+            self.point_size,
+            self.face_index,
+            self.face_count
+        )?;
+        writeln!(
+            f,
+            "fixed width: {}, scalable: {}",
+            yes_no(self.is_fixed_width),
+            yes_no(self.is_scalable)
+        )?;
+        writeln!(
+            f,
+            "height: {}, ascent: {}, descent: {}, line skip: {}",
+            self.height, self.ascent, self.descent, self.line_skip
+        )?;
+        write!(
+            f,
+            "{} characters in {} ranges",
+            self.char_count,
+            self.coverage.len()
+        )?;
+        for &(first, last) in &self.coverage {
+            if first == last {
+                write!(f, "\n  U+{:04X}", first as u32)?;
+            } else {
+                write!(f, "\n  U+{:04X}-U+{:04X}", first as u32, last as u32)?;
+            }
+        }
+        Ok(())
+    }
+}
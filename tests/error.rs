@@ -0,0 +1,22 @@
+extern crate sdl2;
+
+use std::thread;
+
+#[test]
+fn error_is_per_thread() {
+    let threads: Vec<_> = (0..4)
+        .map(|i| {
+            thread::spawn(move || {
+                let message = format!("error from thread {}", i);
+                for _ in 0..100 {
+                    sdl2::set_error(&message).unwrap();
+                    thread::yield_now();
+                    assert_eq!(sdl2::get_error(), message);
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+}
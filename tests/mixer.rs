@@ -0,0 +1,91 @@
+#![cfg(feature = "mixer")]
+extern crate sdl2;
+
+use sdl2::mixer::{self, Channel, Chunk, Music};
+use std::io;
+use std::sync::mpsc;
+use std::sync::Mutex;
+
+// Some tests open the audio device while others expect it to be closed, and
+// tests run in parallel.
+static AUDIO_LOCK: Mutex<()> = Mutex::new(());
+
+#[test]
+fn decoder_out_of_range() {
+    assert_eq!(mixer::get_chunk_decoder(i32::MAX), "");
+    assert_eq!(mixer::get_music_decoder(-1), "");
+}
+
+#[test]
+fn load_with_nul_in_path() {
+    assert!(Chunk::from_file("sound\0.wav").is_err());
+    assert!(Music::from_file("music\0.ogg").is_err());
+    assert!(Music::set_command("player\0").is_err());
+    assert!(mixer::set_soundfonts("font\0.sf2").is_err());
+    assert!(mixer::set_timidity_config("timidity\0.cfg").is_err());
+}
+
+#[test]
+fn load_from_empty_or_endless_reader() {
+    assert_eq!(
+        Chunk::from_reader(io::empty(), None).err(),
+        Some("empty sound data".to_owned())
+    );
+    assert_eq!(
+        Music::from_reader(io::empty(), None).err(),
+        Some("empty music data".to_owned())
+    );
+    assert!(Music::from_reader(io::repeat(0), Some(1024)).is_err());
+}
+
+#[test]
+fn channel_speed_is_validated() {
+    let channel = Channel(7);
+    assert!(Channel::all().set_speed(2.0).is_err());
+    assert!(channel.set_speed(0.0).is_err());
+    assert!(channel.set_speed(f32::NAN).is_err());
+    assert_eq!(channel.speed(), 1.0);
+    channel.set_speed(0.5).unwrap();
+    assert_eq!(channel.speed(), 0.5);
+    channel.set_speed(1.0).unwrap();
+    assert_eq!(channel.speed(), 1.0);
+}
+
+#[test]
+fn raw_buffer_checked_needs_open_audio() {
+    let _guard = AUDIO_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+    let buffer = vec![0i16; 4].into_boxed_slice();
+    assert!(Chunk::from_raw_buffer_checked(buffer, 44_100, 2).is_err());
+}
+
+#[test]
+fn music_position_needs_playing_music() {
+    assert_eq!(Music::position(), None);
+}
+
+#[test]
+fn channel_finished_ex_gets_the_chunk() {
+    let _guard = AUDIO_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+    let sdl = sdl2::init().unwrap();
+    let audio = sdl.audio().unwrap();
+    audio.set_audio_driver("dummy").unwrap();
+    mixer::open_audio(44_100, mixer::AUDIO_S16SYS, 2, 1_024).unwrap();
+    mixer::allocate_channels(4);
+
+    let chunk = Chunk::from_raw_buffer(vec![0i16; 44_100].into_boxed_slice()).unwrap();
+    let (sender, receiver) = mpsc::channel();
+    mixer::set_channel_finished_ex(move |channel, chunk| {
+        // raw pointers aren't Send
+        let _ = sender.send((channel, chunk.map(|chunk| chunk.raw as usize)));
+    });
+    let channel = Channel::all().play(&chunk, -1).unwrap();
+    // halting calls the hook on this thread
+    channel.halt();
+    assert_eq!(receiver.try_recv(), Ok((channel, Some(chunk.raw as usize))));
+
+    mixer::unset_channel_finished();
+    let channel = Channel::all().play(&chunk, -1).unwrap();
+    channel.halt();
+    assert!(receiver.try_recv().is_err());
+    mixer::close_audio();
+}
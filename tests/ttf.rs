@@ -0,0 +1,738 @@
+#![cfg(feature = "ttf")]
+extern crate sdl2;
+
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::surface::Surface;
+use sdl2::ttf::{
+    AsciiAtlas, Font, FontError, FontStyle, Label, RenderMode, RenderQuality, Sdl2TtfContext,
+    SharedTextCache, TextLog,
+};
+use std::env;
+use std::io;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Mutex;
+
+// SDL2_TTF can only be initialized once at a time, while tests run in
+// parallel.
+static TTF_LOCK: Mutex<()> = Mutex::new(());
+
+/// Runs the given test with `SDL2_TTF` initialized.
+fn with_context<F: FnOnce(&Sdl2TtfContext)>(f: F) {
+    let _guard = TTF_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+    let ttf_context = sdl2::ttf::init().unwrap();
+    f(&ttf_context);
+}
+
+/// Runs the given test with a font loaded from the `SDL2_TTF_TEST_FONT`
+/// environment variable. The test is skipped if it is not set.
+fn with_font<F: FnOnce(&mut Font)>(f: F) {
+    let path = match env::var("SDL2_TTF_TEST_FONT") {
+        Ok(path) => path,
+        Err(_) => return,
+    };
+    with_context(|ttf_context| {
+        let mut font = ttf_context.load_font(path, 16).unwrap();
+        f(&mut font);
+    });
+}
+
+fn assert_empty_render(font: &Font, surface: Surface) {
+    assert_eq!(surface.size(), (1, font.height() as u32));
+    surface.with_lock(|pixels| {
+        assert!(pixels.iter().all(|&byte| byte == 0));
+    });
+}
+
+#[test]
+fn render_empty_string_solid() {
+    with_font(|font| {
+        let surface = font.render("").solid(Color::WHITE).unwrap();
+        assert_empty_render(font, surface);
+    });
+}
+
+#[test]
+fn render_empty_string_shaded() {
+    with_font(|font| {
+        let surface = font.render("").shaded(Color::WHITE, Color::BLACK).unwrap();
+        assert_empty_render(font, surface);
+    });
+}
+
+#[test]
+fn render_empty_string_blended() {
+    with_font(|font| {
+        let surface = font.render("").blended(Color::WHITE).unwrap();
+        assert_empty_render(font, surface);
+        let surface = font.render_latin1(b"").blended(Color::WHITE).unwrap();
+        assert_empty_render(font, surface);
+    });
+}
+
+#[test]
+fn render_shaded_background_is_opaque() {
+    with_font(|font| {
+        let background = Color::RGBA(10, 20, 30, 0);
+        let surface = font
+            .render("Hello")
+            .shaded(Color::WHITE, background)
+            .unwrap()
+            .convert_format(PixelFormatEnum::ARGB8888)
+            .unwrap();
+        // the top-left corner is part of the background
+        surface.with_lock(|pixels| {
+            let pixel = u32::from_ne_bytes([pixels[0], pixels[1], pixels[2], pixels[3]]);
+            assert_eq!(pixel, 0xff0a_141e);
+        });
+    });
+}
+
+#[test]
+fn render_shaded_trimmed_is_within_line_box() {
+    with_font(|font| {
+        let full = font
+            .render("_a_")
+            .shaded(Color::WHITE, Color::BLACK)
+            .unwrap();
+        let trimmed = font
+            .render("_a_")
+            .shaded_trimmed(Color::WHITE, Color::BLACK)
+            .unwrap();
+        assert!(trimmed.width() <= full.width());
+        assert!(trimmed.height() < full.height());
+    });
+}
+
+#[test]
+fn render_blended_rgba_bytes_are_tightly_packed() {
+    with_font(|font| {
+        let (bytes, width, height) = font
+            .render("Hello")
+            .blended_rgba_bytes(Color::RGB(255, 0, 0))
+            .unwrap();
+        assert_eq!(bytes.len(), width as usize * height as usize * 4);
+        // every drawn pixel is red
+        assert!(bytes
+            .chunks(4)
+            .filter(|pixel| pixel[3] > 0)
+            .all(|pixel| pixel[..3] == [255, 0, 0]));
+    });
+}
+
+#[test]
+fn combined_style_round_trips() {
+    with_font(|font| {
+        let style = FontStyle::combine(&[FontStyle::BOLD, FontStyle::ITALIC]);
+        assert_eq!(style, FontStyle::BOLD | FontStyle::ITALIC);
+        font.set_style(style);
+        assert_eq!(font.get_style(), style);
+    });
+}
+
+#[test]
+fn render_blended_underlined_draws_full_line() {
+    with_font(|font| {
+        let surface = font
+            .render("Hello")
+            .blended_underlined(Color::RGB(255, 0, 0), 2, Some(1))
+            .unwrap();
+        let row = (font.ascent() + 1) as usize;
+        let pitch = surface.pitch() as usize;
+        let width = surface.width() as usize;
+        surface.with_lock(|pixels| {
+            let line = &pixels[row * pitch..row * pitch + width * 4];
+            assert!(line.chunks(4).all(|pixel| u32::from_ne_bytes([
+                pixel[0], pixel[1], pixel[2], pixel[3]
+            ]) == 0xffff_0000));
+        });
+    });
+}
+
+#[test]
+fn render_mask_holds_alpha() {
+    with_font(|font| {
+        let blended = font.render("Hello").blended(Color::WHITE).unwrap();
+        let mask = font.render("Hello").mask().unwrap();
+        assert_eq!(mask.pixel_format_enum(), PixelFormatEnum::Index8);
+        assert_eq!(mask.size(), blended.size());
+        let (width, height) = mask.size();
+        blended.with_lock(|colors| {
+            mask.with_lock(|alphas| {
+                for y in 0..height as usize {
+                    for x in 0..width as usize {
+                        let pixel = &colors[y * blended.pitch() as usize + x * 4..][..4];
+                        let pixel = u32::from_ne_bytes([pixel[0], pixel[1], pixel[2], pixel[3]]);
+                        assert_eq!(alphas[y * mask.pitch() as usize + x], (pixel >> 24) as u8);
+                    }
+                }
+            });
+        });
+    });
+}
+
+#[test]
+fn rendered_surface_is_owned() {
+    with_font(|font| {
+        let surface = font.render("Hello").blended(Color::WHITE).unwrap();
+        // the `Surface` holds the only reference to the SDL surface, so the
+        // SDL surface is freed when the last clone of its context is dropped
+        assert_eq!(unsafe { (*surface.raw()).refcount }, 1);
+        let context = surface.context();
+        assert_eq!(Rc::strong_count(&context), 2);
+        drop(surface);
+        assert_eq!(Rc::strong_count(&context), 1);
+    });
+}
+
+#[test]
+fn max_line_width_of_multiline_text() {
+    with_font(|font| {
+        let (widest, _) = font.size_of("Hello world").unwrap();
+        let text = "Hello\r\nHello world\n\nworld";
+        assert_eq!(font.max_line_width(text).unwrap(), widest);
+        assert_eq!(font.line_count(text), 4);
+        assert_eq!(font.max_line_width("").unwrap(), 0);
+    });
+}
+
+#[test]
+fn tint_surface_multiplies_channels() {
+    let mut white = Surface::new(2, 1, PixelFormatEnum::ARGB8888).unwrap();
+    white.with_lock_mut(|pixels| {
+        pixels[..4].copy_from_slice(&0xffff_ffffu32.to_ne_bytes());
+        pixels[4..8].copy_from_slice(&0x80ff_ffffu32.to_ne_bytes());
+    });
+    let tinted = sdl2::ttf::tint_surface(&white, (255, 0, 128)).unwrap();
+    assert_eq!(tinted.pixel_format_enum(), PixelFormatEnum::ARGB8888);
+    tinted.with_lock(|pixels| {
+        assert_eq!(pixels[..4], 0xffff_0080u32.to_ne_bytes());
+        assert_eq!(pixels[4..8], 0x80ff_0080u32.to_ne_bytes());
+    });
+}
+
+#[test]
+fn compose_baseline_fits_runs() {
+    with_font(|font| {
+        let word = font.render("Hello").blended(Color::WHITE).unwrap();
+        let (width, height) = word.size();
+        let runs = [
+            (word, font.ascent()),
+            (font.render("world").solid(Color::WHITE).unwrap(), 0),
+        ];
+        let line = sdl2::ttf::compose_baseline(&runs).unwrap();
+        assert_eq!(line.width(), width + runs[1].0.width());
+        // the second run hangs below the baseline of the first one
+        assert_eq!(line.height() as i32, font.ascent() + height as i32);
+    });
+}
+
+#[test]
+fn load_font_from_search_path() {
+    let path = match env::var("SDL2_TTF_TEST_FONT") {
+        Ok(path) => PathBuf::from(path),
+        Err(_) => return,
+    };
+    let dir = path.parent().unwrap();
+    let name = path.file_name().unwrap();
+    with_context(|ttf_context| {
+        assert!(ttf_context.load_font_in(dir, name, 16).is_ok());
+        sdl2::ttf::set_font_search_path(dir);
+        let font = ttf_context.load_font(name, 16);
+        sdl2::ttf::clear_font_search_path();
+        assert!(font.is_ok());
+    });
+}
+
+#[test]
+fn glyph_coverage_is_tightly_packed() {
+    with_font(|font| {
+        let (coverage, width, height) = font.glyph_coverage('A').unwrap();
+        assert_eq!(coverage.len(), width as usize * height as usize);
+        assert!(coverage.iter().any(|&alpha| alpha == 255));
+    });
+}
+
+#[test]
+fn coverage_ranges_are_sorted_and_provided() {
+    with_font(|font| {
+        let ranges = font.coverage_ranges(Some('\u{ff}'));
+        assert!(ranges
+            .iter()
+            .any(|&(first, last)| first <= 'A' && 'Z' <= last));
+        assert!(ranges.windows(2).all(|pair| pair[0].1 < pair[1].0));
+        for &(first, last) in &ranges {
+            assert!(first <= last && last <= '\u{ff}');
+            assert!(font.find_glyph(first).is_some());
+            assert!(font.find_glyph(last).is_some());
+        }
+    });
+}
+
+#[test]
+fn grapheme_width_precomposed_and_decomposed() {
+    with_font(|font| {
+        let precomposed = font.grapheme_width("\u{e9}").unwrap();
+        let decomposed = font.grapheme_width("e\u{301}").unwrap();
+        assert!(precomposed > 0);
+        assert_eq!(precomposed, decomposed);
+    });
+}
+
+#[test]
+fn outline_font_is_scalable() {
+    with_font(|font| assert!(font.is_scalable()));
+}
+
+#[test]
+fn load_font_with_nul_in_path() {
+    with_context(|ttf_context| {
+        assert!(ttf_context.load_font("font\0.ttf", 16).is_err());
+        assert!(ttf_context.load_font_at_index("font\0.ttf", 0, 16).is_err());
+    });
+}
+
+#[test]
+fn render_text_with_nul() {
+    with_font(|font| {
+        match font.render("a\0b").blended(Color::WHITE) {
+            Err(FontError::InvalidUtf8Text(_)) => {}
+            _ => panic!("expected an InvalidUtf8Text error"),
+        }
+        assert!(font.render_char('\0').solid(Color::WHITE).is_err());
+        assert!(font.size_of("a\0b").is_err());
+        match font
+            .render_latin1(b"a\0b")
+            .blended_justified(Color::WHITE, 100)
+        {
+            Err(FontError::InvalidLatin1Text(_)) => {}
+            _ => panic!("expected an InvalidLatin1Text error"),
+        }
+        match font.render("a\0b").blended_vertical(Color::WHITE) {
+            Err(FontError::InvalidUtf8Text(_)) => {}
+            _ => panic!("expected an InvalidUtf8Text error"),
+        }
+        assert!(font.caret_positions("a\0b").is_err());
+        assert!(font.max_line_width("a\nb\0c").is_err());
+    });
+}
+
+#[test]
+fn load_font_from_empty_or_endless_reader() {
+    with_context(|ttf_context| {
+        assert_eq!(
+            ttf_context
+                .load_font_from_reader(io::empty(), 16, None)
+                .err(),
+            Some("empty font data".to_owned())
+        );
+        assert!(ttf_context
+            .load_font_from_reader(io::repeat(0), 16, Some(1024))
+            .is_err());
+    });
+}
+
+#[test]
+fn ascii_atlas_draws_with_advances() {
+    with_font(|font| {
+        let mut atlas = AsciiAtlas::new(font).unwrap();
+        let text = "FPS: 60";
+        let expected: i32 = text.chars().map(|ch| font.advance(ch).unwrap()).sum();
+        assert_eq!(atlas.width_of(text), expected);
+        assert_eq!(atlas.width_of("\u{e9}"), font.advance('?').unwrap());
+
+        let mut target = Surface::new(200, 50, PixelFormatEnum::ARGB8888).unwrap();
+        let end = atlas
+            .draw_str(&mut target, 10, 5, text, Color::RGB(255, 0, 0))
+            .unwrap();
+        assert_eq!(end, 10 + expected);
+        target.with_lock(|pixels| {
+            let drawn = pixels
+                .chunks(4)
+                .map(|p| u32::from_ne_bytes([p[0], p[1], p[2], p[3]]))
+                .filter(|&pixel| pixel >> 24 != 0);
+            // the glyphs are tinted red
+            assert!(drawn.map(|pixel| pixel & 0xffff).all(|gb| gb == 0));
+        });
+    });
+}
+
+#[test]
+fn render_blended_vertical_stacks_characters() {
+    with_font(|font| {
+        let surface = font.render("Hi!").blended_vertical(Color::WHITE).unwrap();
+        let line_skip = font.recommended_line_spacing().max(font.height());
+        assert_eq!(surface.height() as i32, 3 * line_skip);
+        assert!(surface.width() < font.size_of("Hi!").unwrap().0);
+    });
+}
+
+#[test]
+fn render_str_follows_render_quality() {
+    with_font(|font| {
+        assert_eq!(font.render_quality(), RenderQuality::High);
+        let blended = font.render_str("Hello", Color::WHITE).unwrap();
+        assert_eq!(blended.pixel_format_enum(), PixelFormatEnum::ARGB8888);
+        font.set_render_quality(RenderQuality::Fast);
+        let solid = font.render_str("Hello", Color::WHITE).unwrap();
+        assert_eq!(solid.pixel_format_enum(), PixelFormatEnum::Index8);
+    });
+}
+
+#[test]
+fn auto_render_str_follows_point_size() {
+    with_font(|font| {
+        assert_eq!(font.auto_solid_below(), 12);
+        let blended = font.auto_render_str("Hello", Color::WHITE).unwrap();
+        assert_eq!(blended.pixel_format_enum(), PixelFormatEnum::ARGB8888);
+        font.set_auto_solid_below(20);
+        let solid = font.auto_render_str("Hello", Color::WHITE).unwrap();
+        assert_eq!(solid.pixel_format_enum(), PixelFormatEnum::Index8);
+    });
+}
+
+#[test]
+fn render_blended_gradients() {
+    with_font(|font| {
+        let plain = font.render("Title").blended(Color::WHITE).unwrap();
+        let vertical = font
+            .render("Title")
+            .blended_gradient(Color::RED, Color::BLUE)
+            .unwrap();
+        let horizontal = font
+            .render("Title")
+            .blended_gradient_horizontal((0, 255, 0), (0, 0, 255))
+            .unwrap();
+        assert_eq!(vertical.size(), plain.size());
+        assert_eq!(horizontal.size(), plain.size());
+        let pitch = vertical.pitch() as usize;
+        let height = vertical.height() as usize;
+        vertical.with_lock(|pixels| {
+            plain.with_lock(|expected| {
+                let pixels = pixels.chunks(4).zip(expected.chunks(4));
+                for (index, (bytes, white)) in pixels.enumerate() {
+                    let pixel = u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+                    let white = u32::from_ne_bytes([white[0], white[1], white[2], white[3]]);
+                    // the coverage is kept, and the color moves from red to blue
+                    assert_eq!(pixel >> 24, white >> 24);
+                    let y = index * 4 / pitch;
+                    if y == 0 {
+                        assert_eq!(pixel & 0xff_ffff, 0xff_0000);
+                    } else if y == height - 1 {
+                        assert_eq!(pixel & 0xff_ffff, 0x00_00ff);
+                    }
+                }
+            })
+        });
+    });
+}
+
+#[test]
+fn caret_positions_of_right_to_left_text() {
+    with_font(|font| {
+        let text = "\u{5e9}\u{5dc}\u{5d5}\u{5dd}";
+        let reversed: String = text.chars().rev().collect();
+        let width = font.size_of(&reversed).unwrap().0 as i32;
+        let positions = font.caret_positions_rtl(text).unwrap();
+        assert_eq!(positions.len(), 5);
+        assert_eq!(positions[0], width);
+        assert_eq!(positions[4], 0);
+        assert!(positions.windows(2).all(|pair| pair[0] >= pair[1]));
+        assert_eq!(font.caret_at_rtl(text, width + 10).unwrap(), 0);
+        assert_eq!(font.caret_at_rtl(text, -10).unwrap(), 4);
+        // laid out in logical order, the text measures from the left
+        let ltr = font.caret_positions(text).unwrap();
+        assert_eq!(ltr[0], 0);
+        assert!(ltr.windows(2).all(|pair| pair[0] <= pair[1]));
+    });
+}
+
+#[test]
+fn render_blended_tracked() {
+    with_font(|font| {
+        let (width, height) = font.size_of_tracked("Wide", 0).unwrap();
+        let (wide, _) = font.size_of_tracked("Wide", 5).unwrap();
+        let (tight, _) = font.size_of_tracked("Wide", -1).unwrap();
+        assert_eq!(wide, width + 15);
+        assert_eq!(tight, width - 3);
+        assert_eq!(height as i32, font.height());
+        // letters can't be pulled on top of each other
+        let (floor, _) = font.size_of_tracked("Wide", -1000).unwrap();
+        assert!(floor > 0);
+        let surface = font
+            .render("Wide")
+            .blended_tracked(Color::WHITE, 5)
+            .unwrap();
+        assert_eq!(surface.size(), (wide, height));
+        assert_eq!(font.size_of_tracked("", 5).unwrap().0, 0);
+    });
+}
+
+#[test]
+fn render_blended_at_baseline() {
+    with_font(|font| {
+        let mut target = Surface::new(64, 64, PixelFormatEnum::ARGB8888).unwrap();
+        let baseline = 40;
+        font.render("_")
+            .blended_at(Color::WHITE, &mut target, 4, baseline)
+            .unwrap();
+        let pitch = target.pitch() as usize;
+        target.with_lock(|pixels| {
+            let drawn_rows: Vec<usize> = (0..64)
+                .filter(|&y| {
+                    pixels[y * pitch..y * pitch + 64 * 4]
+                        .iter()
+                        .any(|&b| b != 0)
+                })
+                .collect();
+            assert!(!drawn_rows.is_empty());
+            // the line is drawn within the font's line around the baseline
+            let top = (baseline - font.ascent()) as usize;
+            let bottom = (baseline - font.descent()) as usize;
+            assert!(drawn_rows.iter().all(|&y| y >= top && y <= bottom));
+        });
+        // text partly or wholly outside of the target is clipped
+        font.render("Hello")
+            .blended_at(Color::WHITE, &mut target, -10, 2)
+            .unwrap();
+        font.render("Hello")
+            .blended_at(Color::WHITE, &mut target, 1000, 1000)
+            .unwrap();
+    });
+}
+
+#[cfg(feature = "unicode-normalization")]
+#[test]
+fn normalization_makes_nfd_render_as_nfc() {
+    use sdl2::ttf::NormalizationForm;
+    with_font(|font| {
+        assert_eq!(font.normalization(), NormalizationForm::None);
+        font.set_normalization(NormalizationForm::Nfc);
+        let composed = font.size_of("caf\u{e9}").unwrap();
+        assert_eq!(font.size_of("cafe\u{301}").unwrap(), composed);
+        let rendered = font.render("cafe\u{301}").blended(Color::WHITE).unwrap();
+        assert_eq!(rendered.size(), composed);
+    });
+}
+
+#[test]
+fn caret_positions_measure_prefixes() {
+    with_font(|font| {
+        let positions = font.caret_positions("AVA").unwrap();
+        assert_eq!(positions.len(), 4);
+        assert_eq!(positions[0], 0);
+        assert_eq!(positions[3], font.size_of("AVA").unwrap().0 as i32);
+        assert!(positions.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(font.caret_at("AVA", -10).unwrap(), 0);
+        assert_eq!(font.caret_at("AVA", positions[2] + 1).unwrap(), 2);
+        assert_eq!(font.caret_at("AVA", 1000).unwrap(), 3);
+        assert_eq!(font.caret_positions("").unwrap(), vec![0]);
+    });
+}
+
+#[test]
+fn render_blended_scaled_is_larger() {
+    with_font(|font| {
+        let normal = font.render("Hello").blended(Color::WHITE).unwrap();
+        let scaled = font
+            .render("Hello")
+            .blended_scaled(Color::WHITE, 2)
+            .unwrap();
+        assert!(scaled.height() >= normal.height() * 3 / 2);
+        assert!(font
+            .render("Hello")
+            .blended_scaled(Color::WHITE, 0)
+            .is_err());
+    });
+}
+
+#[test]
+fn effective_line_skip_includes_outline() {
+    with_font(|font| {
+        assert_eq!(font.effective_line_skip(), font.recommended_line_spacing());
+        font.set_outline_width(2);
+        assert_eq!(
+            font.effective_line_skip(),
+            font.recommended_line_spacing() + 4
+        );
+        let line = font.render("Hello").blended(Color::WHITE).unwrap();
+        assert_eq!(line.height() as i32, font.height() + 4);
+    });
+}
+
+#[test]
+fn render_with_color_or_tuples() {
+    with_font(|font| {
+        let pixels = |surface: Surface| surface.with_lock(|pixels| pixels.to_vec());
+        let color = font.render("Hello").blended(Color::RGB(255, 0, 0)).unwrap();
+        let rgb = font.render("Hello").blended((255, 0, 0)).unwrap();
+        let rgba = font.render("Hello").blended((255, 0, 0, 255)).unwrap();
+        let expected = pixels(color);
+        assert_eq!(pixels(rgb), expected);
+        assert_eq!(pixels(rgba), expected);
+    });
+}
+
+#[test]
+fn text_log_grows_with_lines() {
+    with_font(|font| {
+        let mut log = TextLog::new(8).unwrap();
+        let line_skip = font.recommended_line_spacing().max(font.height()) as u32;
+        for _ in 0..5 {
+            log.push_line(font, "Hello world", Color::WHITE).unwrap();
+        }
+        assert_eq!(log.line_count(), 5);
+        assert_eq!(log.height(), 5 * line_skip);
+        assert!(log.surface().height() >= log.height());
+        assert_eq!(
+            log.surface().width(),
+            font.size_of("Hello world").unwrap().0
+        );
+        log.clear();
+        assert_eq!(log.height(), 0);
+    });
+}
+
+#[test]
+fn render_blended_justified_fills_lines() {
+    with_font(|font| {
+        let text = "the quick brown fox jumps over the lazy dog\nend";
+        let width = font.size_of("the quick brown fox").unwrap().0 + 7;
+        let surface = font
+            .render(text)
+            .blended_justified(Color::WHITE, width)
+            .unwrap();
+        assert_eq!(surface.width(), width);
+        let line_skip = font.recommended_line_spacing().max(font.height());
+        assert_eq!(surface.height() as i32 % line_skip, 0);
+        assert!(surface.height() as i32 >= 3 * line_skip);
+        // the first line is justified, so it reaches the right margin
+        let pitch = surface.pitch() as usize;
+        surface.with_lock(|pixels| {
+            let margin = width as usize - 4..width as usize;
+            let reaches_margin = (0..font.height() as usize).any(|y| {
+                margin.clone().any(|x| {
+                    let offset = y * pitch + x * 4;
+                    u32::from_ne_bytes([
+                        pixels[offset],
+                        pixels[offset + 1],
+                        pixels[offset + 2],
+                        pixels[offset + 3],
+                    ]) >> 24
+                        != 0
+                })
+            });
+            assert!(reaches_margin);
+        });
+    });
+}
+
+#[test]
+fn label_renders_again_only_on_change() {
+    with_font(|font| {
+        let mut label = Label::new(font, "Score: 0", Color::WHITE);
+        assert!(label.is_dirty());
+        let (width, _) = label.size().unwrap();
+        assert_eq!(width, font.size_of("Score: 0").unwrap().0);
+        label.set_text("Score: 0");
+        label.set_color(Color::WHITE);
+        assert!(!label.is_dirty());
+
+        label.set_style(FontStyle::BOLD);
+        assert!(label.is_dirty());
+        label.surface().unwrap();
+        // the style of the font is left as it was
+        assert_eq!(font.get_style(), FontStyle::NORMAL);
+
+        label.set_text("Score: 10");
+        assert!(label.is_dirty());
+        assert_eq!(label.text(), "Score: 10");
+    });
+}
+
+#[test]
+fn shared_text_cache_shares_surfaces() {
+    with_font(|font| {
+        let mut cache = SharedTextCache::new(2);
+        let first = cache
+            .render(font, "Hello", Color::WHITE, RenderMode::Blended)
+            .unwrap();
+        let second = cache
+            .render(font, "Hello", Color::WHITE, RenderMode::Blended)
+            .unwrap();
+        assert!(Rc::ptr_eq(&first, &second));
+        cache
+            .render(font, "Hello", Color::RGB(255, 0, 0), RenderMode::Blended)
+            .unwrap();
+        cache
+            .render(font, "world", Color::WHITE, RenderMode::Solid)
+            .unwrap();
+        assert_eq!(cache.len(), 2);
+        // the evicted surface lives on while it is used
+        let third = cache
+            .render(font, "Hello", Color::WHITE, RenderMode::Blended)
+            .unwrap();
+        assert!(!Rc::ptr_eq(&first, &third));
+        assert_eq!(first.size(), third.size());
+    });
+}
+
+#[test]
+fn render_whitespace_blended_is_transparent() {
+    with_font(|font| {
+        let surface = font.render("    ").blended(Color::WHITE).unwrap();
+        assert_eq!(surface.size(), font.size_of("    ").unwrap());
+        assert!(surface.width() > 1);
+        surface.with_lock(|pixels| {
+            assert!(pixels
+                .chunks(4)
+                .all(|p| u32::from_ne_bytes([p[0], p[1], p[2], p[3]]) >> 24 == 0));
+        });
+    });
+}
+
+#[test]
+fn render_blended_rotated_moves_pixels() {
+    with_font(|font| {
+        let read = |surface: &Surface, x: u32, y: u32| {
+            surface.with_lock(|pixels| {
+                let offset = (y * surface.pitch() + x * 4) as usize;
+                u32::from_ne_bytes([
+                    pixels[offset],
+                    pixels[offset + 1],
+                    pixels[offset + 2],
+                    pixels[offset + 3],
+                ])
+            })
+        };
+        let upright = font.render("Hello").blended(Color::WHITE).unwrap();
+        let (width, height) = upright.size();
+        for &turns in &[1, 2, 3] {
+            let rotated = font
+                .render("Hello")
+                .blended_rotated(Color::WHITE, turns)
+                .unwrap();
+            let expected_size = if turns == 2 {
+                (width, height)
+            } else {
+                (height, width)
+            };
+            assert_eq!(rotated.size(), expected_size);
+            for y in 0..height {
+                for x in 0..width {
+                    let (rx, ry) = match turns {
+                        1 => (height - 1 - y, x),
+                        2 => (width - 1 - x, height - 1 - y),
+                        _ => (y, width - 1 - x),
+                    };
+                    assert_eq!(read(&upright, x, y), read(&rotated, rx, ry));
+                }
+            }
+        }
+        let full_turn = font
+            .render("Hello")
+            .blended_rotated(Color::WHITE, 4)
+            .unwrap();
+        assert_eq!(full_turn.size(), (width, height));
+    });
+}
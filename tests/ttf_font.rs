@@ -0,0 +1,275 @@
+#![cfg(feature = "ttf")]
+//! Tests against the font bundled in `assets`, which run wherever `SDL2_ttf`
+//! is available, unlike the tests of `ttf.rs` that need a font to be given.
+extern crate sdl2;
+
+use sdl2::pixels::Color;
+use sdl2::rwops::RWops;
+use sdl2::ttf::{AlphaMode, Font, RenderMode, SharedTextCache, TextCache};
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Mutex;
+
+// SDL2_TTF can only be initialized once at a time, while tests run in
+// parallel.
+static TTF_LOCK: Mutex<()> = Mutex::new(());
+
+fn font_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("assets/DejaVuSansMono.ttf")
+}
+
+/// Runs the given test with DejaVu Sans Mono loaded at 16 points.
+fn with_bundled_font<F: FnOnce(&Font)>(f: F) {
+    let _guard = TTF_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+    let ttf_context = sdl2::ttf::init().unwrap();
+    let font = ttf_context.load_font(font_path(), 16).unwrap();
+    f(&font);
+}
+
+#[test]
+fn bundled_font_faces() {
+    with_bundled_font(|font| {
+        assert_eq!(font.face_family_name().as_deref(), Some("DejaVu Sans Mono"));
+        assert!(font.face_is_fixed_width());
+        assert!(font.height() > 0);
+        assert_eq!(font.height(), font.ascent() - font.descent());
+    });
+}
+
+#[test]
+fn glyph_metrics_of_char() {
+    with_bundled_font(|font| {
+        let a = font.find_glyph_metrics('A').unwrap();
+        assert!(a.maxx > a.minx);
+        assert!(a.maxy > a.miny);
+        assert!(a.maxy <= font.ascent());
+        assert!(a.advance > 0);
+        // every glyph of a monospace font has the same advance
+        let i = font.find_glyph_metrics('i').unwrap();
+        assert_eq!(i.advance, a.advance);
+        assert_eq!(font.advance('A'), Some(a.advance));
+    });
+}
+
+#[test]
+fn advance_of_missing_glyph() {
+    with_bundled_font(|font| {
+        assert!(font.advance('?').is_some());
+        assert_eq!(font.advance('\u{4e00}'), None);
+        // beyond the Basic Multilingual Plane, rather than U+F600
+        assert_eq!(font.advance('\u{1f600}'), None);
+    });
+}
+
+#[test]
+fn size_of_text() {
+    with_bundled_font(|font| {
+        let advance = font.find_glyph_metrics('A').unwrap().advance;
+        let (width, height) = font.size_of("Hello").unwrap();
+        assert_eq!(width as i32, 5 * advance);
+        assert_eq!(height as i32, font.height());
+        assert_eq!(font.size_of_latin1(b"Hello").unwrap(), (width, height));
+    });
+}
+
+#[test]
+fn render_round_trip() {
+    with_bundled_font(|font| {
+        let surface = font.render("Hello").blended(Color::WHITE).unwrap();
+        assert_eq!(surface.size(), font.size_of("Hello").unwrap());
+        surface.with_lock(|pixels| {
+            // some pixels are drawn, and only in white
+            let drawn: Vec<u32> = pixels
+                .chunks(4)
+                .map(|p| u32::from_ne_bytes([p[0], p[1], p[2], p[3]]))
+                .filter(|&pixel| pixel >> 24 != 0)
+                .collect();
+            assert!(!drawn.is_empty());
+            assert!(drawn.iter().all(|&pixel| pixel & 0xff_ffff == 0xff_ffff));
+        });
+    });
+}
+
+#[test]
+fn glyph_index_tells_notdef_apart() {
+    with_bundled_font(|font| {
+        let index = font.glyph_index('A').unwrap();
+        assert!(index > 0);
+        assert_eq!(font.find_glyph('A'), Some(index as u16));
+        // the font has no CJK ideographs, which map to .notdef
+        assert_eq!(font.glyph_index('\u{4e00}'), Some(0));
+        assert_eq!(font.find_glyph('\u{4e00}'), None);
+        // outside of the Basic Multilingual Plane
+        assert_eq!(font.glyph_index('\u{1f600}'), None);
+        assert_eq!(font.find_glyph('\u{1f600}'), None);
+    });
+}
+
+#[test]
+fn substitute_replaces_missing_chars() {
+    let _guard = TTF_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+    let ttf_context = sdl2::ttf::init().unwrap();
+    let mut font = ttf_context.load_font(font_path(), 16).unwrap();
+    let tofu = font.render("a\u{4e00}b").blended(Color::WHITE).unwrap();
+    assert_eq!(font.substitute(), None);
+
+    font.set_substitute('?');
+    assert_eq!(
+        font.size_of("a\u{4e00}b").unwrap(),
+        font.size_of("a?b").unwrap()
+    );
+    let substituted = font.render("a\u{4e00}b").blended(Color::WHITE).unwrap();
+    let question = font.render("a?b").blended(Color::WHITE).unwrap();
+    assert_eq!(substituted.size(), question.size());
+    substituted.with_lock(|pixels| question.with_lock(|expected| assert_eq!(pixels, expected)));
+
+    // a substitute the face lacks isn't used
+    font.set_substitute('\u{4e01}');
+    let unchanged = font.render("a\u{4e00}b").blended(Color::WHITE).unwrap();
+    unchanged.with_lock(|pixels| tofu.with_lock(|expected| assert_eq!(pixels, expected)));
+
+    // nor is a control character, even if the face maps it
+    font.set_substitute('\0');
+    let unchanged = font.render("a\u{4e00}b").blended(Color::WHITE).unwrap();
+    unchanged.with_lock(|pixels| tofu.with_lock(|expected| assert_eq!(pixels, expected)));
+    assert!(font.size_of("a\u{4e00}b").is_ok());
+}
+
+#[test]
+fn repeated_renders_match() {
+    with_bundled_font(|font| {
+        let first = font.render("Score: 10").blended(Color::WHITE).unwrap();
+        let again = font.render("Score: 10").blended(Color::WHITE).unwrap();
+        font.clear_c_string_cache();
+        let cleared = font.render("Score: 10").blended(Color::WHITE).unwrap();
+        first.with_lock(|pixels| {
+            again.with_lock(|expected| assert_eq!(pixels, expected));
+            cleared.with_lock(|expected| assert_eq!(pixels, expected));
+        });
+        assert_eq!(font.size_of("Score: 10").unwrap(), first.size());
+    });
+}
+
+#[test]
+fn face_index_out_of_range() {
+    let _guard = TTF_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+    let ttf_context = sdl2::ttf::init().unwrap();
+    let font = ttf_context.load_font_at_index(font_path(), 0, 16).unwrap();
+    assert_eq!(font.face_count(), 1);
+    let err = ttf_context
+        .load_font_at_index(font_path(), 1, 16)
+        .err()
+        .unwrap();
+    assert!(err.starts_with("face index out of range"), "{}", err);
+}
+
+#[test]
+fn warm_cache_renders_the_same() {
+    with_bundled_font(|font| {
+        let cold = font.render("Hello").blended(Color::WHITE).unwrap();
+        font.warm_cache("Hello, world!\n").unwrap();
+        font.warm_cache("").unwrap();
+        let warm = font.render("Hello").blended(Color::WHITE).unwrap();
+        cold.with_lock(|pixels| warm.with_lock(|expected| assert_eq!(pixels, expected)));
+    });
+}
+
+#[test]
+fn report_summarizes_the_font() {
+    with_bundled_font(|font| {
+        let report = font.report();
+        assert_eq!(report.family_name.as_deref(), Some("DejaVu Sans Mono"));
+        assert_eq!(report.point_size, 16);
+        assert_eq!(report.face_count, 1);
+        assert!(report.is_fixed_width);
+        assert!(report.is_scalable);
+        assert_eq!(report.height, font.height());
+        assert!(report.char_count >= 95);
+        assert!(report
+            .coverage
+            .iter()
+            .any(|&(first, last)| first <= ' ' && last >= '~'));
+        let text = report.to_string();
+        assert!(text.starts_with("DejaVu Sans Mono Book, 16 pt (face 0 of 1)\n"));
+        assert!(text.contains("fixed width: yes, scalable: yes"));
+    });
+}
+
+#[test]
+fn output_alpha_premultiplies_blended() {
+    let _guard = TTF_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+    let ttf_context = sdl2::ttf::init().unwrap();
+    let mut font = ttf_context.load_font(font_path(), 16).unwrap();
+    let color = Color::RGB(200, 100, 50);
+    assert_eq!(font.output_alpha(), AlphaMode::Straight);
+    let straight = font.render("Alpha").blended(color).unwrap();
+
+    font.set_output_alpha(AlphaMode::Premultiplied);
+    let premultiplied = font.render("Alpha").blended(color).unwrap();
+    let expected = font.render("Alpha").blended_premultiplied(color).unwrap();
+    premultiplied.with_lock(|pixels| {
+        expected.with_lock(|expected| assert_eq!(pixels, expected));
+        straight.with_lock(|straight| assert_ne!(pixels, straight));
+    });
+
+    // solid renderings are left alone
+    font.set_output_alpha(AlphaMode::Straight);
+    let solid = font.render("Alpha").solid(color).unwrap();
+    font.set_output_alpha(AlphaMode::Premultiplied);
+    let still_solid = font.render("Alpha").solid(color).unwrap();
+    solid.with_lock(|pixels| still_solid.with_lock(|expected| assert_eq!(pixels, expected)));
+}
+
+#[test]
+fn shared_text_cache_keys_on_the_source() {
+    let _guard = TTF_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+    let ttf_context = sdl2::ttf::init().unwrap();
+    let data = std::fs::read(font_path()).unwrap();
+    let font = ttf_context.load_font(font_path(), 16).unwrap();
+    let same = ttf_context.load_font(font_path(), 16).unwrap();
+    let loaded = ttf_context
+        .load_font_from_rwops(RWops::from_bytes(&data).unwrap(), 16)
+        .unwrap();
+    let mut cache = SharedTextCache::new(4);
+    let first = cache
+        .render(&font, "Hello", Color::WHITE, RenderMode::Blended)
+        .unwrap();
+    let from_same = cache
+        .render(&same, "Hello", Color::WHITE, RenderMode::Blended)
+        .unwrap();
+    assert!(Rc::ptr_eq(&first, &from_same));
+
+    // the same face and names, but no known source
+    let from_loaded = cache
+        .render(&loaded, "Hello", Color::WHITE, RenderMode::Blended)
+        .unwrap();
+    assert!(!Rc::ptr_eq(&first, &from_loaded));
+    let again = cache
+        .render(&loaded, "Hello", Color::WHITE, RenderMode::Blended)
+        .unwrap();
+    assert!(Rc::ptr_eq(&from_loaded, &again));
+}
+
+#[test]
+fn text_cache_is_cleared_for_another_font() {
+    let _guard = TTF_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+    let ttf_context = sdl2::ttf::init().unwrap();
+    let data = std::fs::read(font_path()).unwrap();
+    let mut cache = TextCache::new(4);
+    {
+        let font = ttf_context
+            .load_font_from_rwops(RWops::from_bytes(&data).unwrap(), 16)
+            .unwrap();
+        cache
+            .render(&font, "Hello", Color::WHITE, RenderMode::Blended)
+            .unwrap();
+    }
+    // the new font may well get the address of the dropped one
+    let font = ttf_context
+        .load_font_from_rwops(RWops::from_bytes(&data).unwrap(), 16)
+        .unwrap();
+    cache
+        .render(&font, "world", Color::WHITE, RenderMode::Blended)
+        .unwrap();
+    assert_eq!(cache.len(), 1);
+}